@@ -0,0 +1,176 @@
+//! Named rendering workloads, to measure the renderer instead of guessing.
+//!
+//! Every workload draws the same thing for a given frame number, so results from two builds
+//! can be compared. Run them headless with the `rael-bench` binary.
+
+use crate::{Color, Rael, RaelError};
+use rand::Rng;
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+const TEXT: &str = "Long ago, two races ruled over Earth: HUMANS and MONSTERS. One day, war broke \
+                    out between the two races. After a long battle, the humans were victorious. \
+                    They sealed the monsters underground with a magic spell.";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Workload {
+    /// Up to a thousand random pixels per frame, never cleared, like `run_stress_test`
+    RandomPixels,
+    /// A full-screen picture moving up one pixel per frame
+    ImageScroll,
+    /// Text appearing one char per frame, like the intro dialogues
+    Typewriter,
+    /// A full-screen picture fading out over and over, like the intro transitions
+    Fade,
+    /// A static background with a small sprite moving on it
+    Static,
+}
+
+impl Workload {
+    pub const ALL: [Workload; 5] = [
+        Workload::RandomPixels,
+        Workload::ImageScroll,
+        Workload::Typewriter,
+        Workload::Fade,
+        Workload::Static,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Workload::RandomPixels => "random-pixels",
+            Workload::ImageScroll => "image-scroll",
+            Workload::Typewriter => "typewriter",
+            Workload::Fade => "fade",
+            Workload::Static => "static",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|w| w.name() == name)
+    }
+
+    // draws a frame and tells which deltarune to render it with
+    fn draw(self, rael: &mut Rael, frame: u32) -> Option<f32> {
+        let (w, h) = (rael.widht as usize, rael.height as usize);
+        match self {
+            Workload::RandomPixels => {
+                let palette = [
+                    Color::new(255, 0, 0),
+                    Color::new(0, 255, 0),
+                    Color::new(0, 0, 255),
+                    Color::new(255, 255, 255),
+                ];
+                for _ in 0..rael.rng.random_range(1..1000) {
+                    let x = rael.rng.random_range(0..w);
+                    let y = rael.rng.random_range(0..h);
+                    let color = palette[rael.rng.random_range(0..palette.len())];
+                    rael.set_pixel(x, y, 0, color);
+                }
+                None
+            }
+            Workload::ImageScroll => {
+                rael.clear();
+                draw_picture(rael, frame as usize);
+                None
+            }
+            Workload::Typewriter => {
+                rael.clear();
+                let shown: String = TEXT.chars().take(frame as usize % TEXT.len()).collect();
+                let columns = w.saturating_sub(4).max(1);
+                let chars: Vec<char> = shown.chars().collect();
+                for (line, chunk) in chars.chunks(columns).enumerate() {
+                    let text: String = chunk.iter().collect();
+                    let (bg, fg) = (Color::new(0, 0, 0), Color::new(255, 255, 255));
+                    rael.set_str(2, 2 + line * 2, 1, bg, fg, &text);
+                }
+                None
+            }
+            Workload::Fade => {
+                rael.force_clear();
+                draw_picture(rael, 0);
+                Some(1.0 - (frame % 20) as f32 / 20.0)
+            }
+            Workload::Static => {
+                rael.clear();
+                draw_picture(rael, 0);
+                let sx = frame as usize % w.saturating_sub(8).max(1);
+                for y in 0..8 {
+                    for x in 0..8 {
+                        rael.set_pixel(sx + x, h / 2 + y, 1, Color::new(255, 255, 0));
+                    }
+                }
+                None
+            }
+        }
+    }
+}
+
+fn draw_picture(rael: &mut Rael, scroll: usize) {
+    for y in 0..rael.height as usize {
+        for x in 0..rael.widht as usize {
+            let py = y + scroll;
+            let checker = ((x / 8 + py / 8) % 2) as u8 * 120;
+            rael.set_pixel(x, y, 0, Color::new((x * 4) as u8, (py * 3) as u8, checker));
+        }
+    }
+}
+
+/// What a workload measured.
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchResult {
+    pub workload: &'static str,
+    pub frames: u32,
+    pub columns: u16,
+    pub rows: u16,
+    pub seconds: f64,
+    pub fps: f64,
+    pub bytes_per_frame: f64,
+    pub cells_per_frame: f64,
+    pub avg_render_ms: f64,
+    pub max_render_ms: f64,
+}
+
+/// Renders `frames` frames of a workload as fast as possible.
+///
+/// The screen and palette get reset first and `rng` is reseeded, so every run draws the same.
+pub fn run_workload(
+    rael: &mut Rael,
+    workload: Workload,
+    frames: u32,
+) -> Result<BenchResult, RaelError> {
+    rael.force_clear();
+    rael.render_blocking(None)?;
+    rael.reseed(0);
+
+    let mut bytes = 0;
+    let mut cells = 0;
+    let mut render_time = Duration::ZERO;
+    let mut max_render = Duration::ZERO;
+    let started = Instant::now();
+
+    for frame in 0..frames {
+        let deltarune = workload.draw(rael, frame);
+        rael.render_blocking(deltarune)?;
+        if let Some(stats) = rael.stats.last() {
+            bytes += stats.bytes_written;
+            cells += stats.cells_changed;
+            render_time += stats.render_time;
+            max_render = max_render.max(stats.render_time);
+        }
+    }
+
+    let seconds = started.elapsed().as_secs_f64();
+    let frames_f = frames.max(1) as f64;
+    Ok(BenchResult {
+        workload: workload.name(),
+        frames,
+        columns: rael.widht,
+        rows: rael.height / 2,
+        seconds,
+        fps: frames as f64 / seconds.max(f64::EPSILON),
+        bytes_per_frame: bytes as f64 / frames_f,
+        cells_per_frame: cells as f64 / frames_f,
+        avg_render_ms: render_time.as_secs_f64() * 1000.0 / frames_f,
+        max_render_ms: max_render.as_secs_f64() * 1000.0,
+    })
+}
@@ -0,0 +1,55 @@
+//! Runs the renderer workloads headless and prints one JSON line per workload.
+//!
+//! `rael-bench [--frames N] [--size COLUMNSxROWS] [workload...]`, every workload runs
+//! when none is given.
+
+use rael::bench::{run_workload, Workload};
+use rael::{MemoryOutput, Rael};
+use std::process::exit;
+
+fn usage() -> ! {
+    let names: Vec<&str> = Workload::ALL.iter().map(|w| w.name()).collect();
+    eprintln!("usage: rael-bench [--frames N] [--size COLUMNSxROWS] [workload...]");
+    eprintln!("workloads: {}", names.join(", "));
+    exit(2);
+}
+
+fn main() -> std::io::Result<()> {
+    let mut frames = 500;
+    let mut size = (200, 60);
+    let mut workloads = Vec::new();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--frames" => {
+                frames = args
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .unwrap_or_else(|| usage());
+            }
+            "--size" => {
+                size = args
+                    .next()
+                    .and_then(|s| {
+                        let (w, h) = s.split_once('x')?;
+                        Some((w.parse().ok()?, h.parse().ok()?))
+                    })
+                    .unwrap_or_else(|| usage());
+            }
+            name => workloads.push(Workload::from_name(name).unwrap_or_else(|| usage())),
+        }
+    }
+    if workloads.is_empty() {
+        workloads = Workload::ALL.to_vec();
+    }
+
+    let output = MemoryOutput::new();
+    let mut rael = Rael::headless(output.clone(), size.0, size.1);
+    for workload in workloads {
+        let result = run_workload(&mut rael, workload, frames)?;
+        output.take();
+        println!("{}", serde_json::to_string(&result)?);
+    }
+    Ok(())
+}
@@ -0,0 +1,101 @@
+//! Shows a game broadcast by `Rael::broadcast`, read-only: nothing typed here reaches
+//! the game.
+//!
+//! `rael-watch ADDR`, with `unix:PATH` for a unix socket or `HOST:PORT` for TCP.
+//! `q`, Esc or Ctrl+C quits.
+
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::style::ResetColor;
+use crossterm::terminal::{
+    self, DisableLineWrap, EnableLineWrap, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use crossterm::{cursor, execute};
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::process::exit;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+fn usage() -> ! {
+    eprintln!("usage: rael-watch ADDR (unix:PATH or HOST:PORT)");
+    exit(2);
+}
+
+fn connect(addr: &str) -> io::Result<Box<dyn Read + Send>> {
+    match addr.strip_prefix("unix:") {
+        #[cfg(unix)]
+        Some(path) => Ok(Box::new(std::os::unix::net::UnixStream::connect(path)?)),
+        #[cfg(not(unix))]
+        Some(_) => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "unix sockets are not available here",
+        )),
+        None => Ok(Box::new(TcpStream::connect(addr)?)),
+    }
+}
+
+fn quits(event: &Event) -> bool {
+    let Event::Key(key) = event else {
+        return false;
+    };
+    key.kind == KeyEventKind::Press
+        && (matches!(key.code, KeyCode::Char('q') | KeyCode::Esc)
+            || key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL))
+}
+
+fn watch(mut stream: Box<dyn Read + Send>) -> io::Result<()> {
+    // the game hanging up ends the watch too
+    let ended = Arc::new(AtomicBool::new(false));
+    let ended_bg = ended.clone();
+    thread::spawn(move || {
+        let mut stdout = io::stdout();
+        let mut buf = [0; 64 * 1024];
+        while let Ok(n @ 1..) = stream.read(&mut buf) {
+            if stdout
+                .write_all(&buf[..n])
+                .and_then(|_| stdout.flush())
+                .is_err()
+            {
+                break;
+            }
+        }
+        ended_bg.store(true, Ordering::SeqCst);
+    });
+
+    while !ended.load(Ordering::SeqCst) {
+        if event::poll(Duration::from_millis(100))? && quits(&event::read()?) {
+            break;
+        }
+    }
+    Ok(())
+}
+
+fn main() -> io::Result<()> {
+    let mut args = std::env::args().skip(1);
+    let (Some(addr), None) = (args.next(), args.next()) else {
+        usage();
+    };
+    let stream = connect(&addr)?;
+
+    terminal::enable_raw_mode()?;
+    execute!(
+        io::stdout(),
+        EnterAlternateScreen,
+        DisableLineWrap,
+        cursor::Hide
+    )?;
+    let result = watch(stream);
+    // keeps the stream from printing over the restored screen
+    let mut stdout = io::stdout().lock();
+    execute!(
+        stdout,
+        ResetColor,
+        EnableLineWrap,
+        LeaveAlternateScreen,
+        cursor::Show
+    )?;
+    terminal::disable_raw_mode()?;
+    result
+}
@@ -2,14 +2,16 @@
 //! function but yknow, i let u guys do the rest...
 //! it uses crossterm for the terminal stuff, here is a simple example to draw an X in the terminal
 //! # Example
-//! ```rust
-//! mod rael;
-//!
-//! use rael::{Color, Rael};
+//! with the `async` feature:
+//! ```rust,no_run
+//! use rael::{Color, Rael, RaelError};
 //! use tokio::time::{sleep, Duration};
 //!
+//! # #[cfg(not(feature = "async"))]
+//! # fn main() {}
+//! # #[cfg(feature = "async")]
 //! #[tokio::main(flavor = "current_thread")]
-//! async fn main() -> std::io::Result<()> {
+//! async fn main() -> Result<(), RaelError> {
 //!     let mut rael = Rael::new(std::io::stdout(), "Rael Simple Example")?;
 //!
 //!     for i in 0..10 {
@@ -17,29 +19,52 @@
 //!         rael.set_pixel(i, 9 - i, 1, Color::new(0, 255, 0));
 //!     }
 //!
-//!     rael.render().await?;
+//!     rael.render(None).await?;
 //!
 //!     sleep(Duration::from_secs(2)).await;
 //!
 //!     rael.clear();
-//!     rael.render().await?;
+//!     rael.render(None).await?;
 //!
 //!     Ok(())
 //! }
 //! ```
 //! the "engine" uses kitties keyboard protocol, and all of the events can be triggered using
 //! `rael.inputs.take_snapshot()` to take the current input
+//! held keys and the ones pressed or released this frame are in `rael.inputs.key(code)`
+//!
+//! the async stuff (the `async` feature) works with whatever runtime, and nothing needs one:
+//! without it, use the blocking versions
+//! ```rust,no_run
+//! use rael::{Color, Rael, RaelError};
+//!
+//! fn main() -> Result<(), RaelError> {
+//!     let mut rael = Rael::new(std::io::stdout(), "Rael Blocking Example")?;
+//!     rael.set_pixel(0, 0, 1, Color::new(255, 0, 0));
+//!     rael.render_blocking(None)?;
+//!     rael.clock.sleep_blocking(std::time::Duration::from_secs(2));
+//!     Ok(())
+//! }
+//! ```
+//!
+//! # Features
+//! the framebuffer and the renderer are always there, `input` is on by default and the rest
+//! is opt-in:
+//! - `input` (default): keyboard and mouse input, input journals, the engine hotkeys
+//! - `async`: async `render`, clock sleeps and game loop, for any runtime
+//! - `capture`: screenshots and GIF/APNG clips
+//! - `server`: playing over telnet with `serve`
+//! - `bench`: the renderer workloads of `rael-bench`
+//! - `figlet-text`: FIGlet fonts for big text
 
+#[cfg(feature = "bench")]
+pub mod bench;
 mod rael;
 pub use rael::*;
 
 use rand::Rng;
-use std::io;
-use std::time::{Duration, Instant};
-
-pub async fn run_stress_test(rael: &mut Rael) -> io::Result<()> {
-    let mut rng = rand::rng();
 
+pub fn run_stress_test(rael: &mut Rael) -> Result<(), RaelError> {
     // Define a fixed palette to pick from
     let palette = [
         Color::new(255, 0, 0),     // Red
@@ -51,33 +76,18 @@ pub async fn run_stress_test(rael: &mut Rael) -> io::Result<()> {
         Color::new(255, 255, 255), // White
     ];
 
-    let mut frame_count = 0;
-    let mut last_check = Instant::now();
-    let mut fps_display = String::from("FPS: 0.00");
+    rael.stats.overlay = true;
 
     loop {
-        let num_pixels = rng.random_range(1..1000);
+        let num_pixels = rael.rng.random_range(1..1000);
 
         for _ in 0..num_pixels {
-            let x = rng.random_range(0..rael.widht as usize);
-            let y = rng.random_range(0..rael.height as usize);
-            let color = palette[rng.random_range(0..palette.len())];
+            let x = rael.rng.random_range(0..rael.widht as usize);
+            let y = rael.rng.random_range(0..rael.height as usize);
+            let color = palette[rael.rng.random_range(0..palette.len())];
             rael.set_pixel(x, y, 0, color);
         }
 
-        frame_count += 1;
-        let elapsed = last_check.elapsed();
-        if elapsed >= Duration::from_secs(1) {
-            let fps = frame_count as f64 / elapsed.as_secs_f64();
-            fps_display = format!("FPS: {:.2}", fps);
-            frame_count = 0;
-            last_check = Instant::now();
-        }
-
-        for (i, c) in fps_display.chars().enumerate() {
-            rael.set_text(i, 0, 255, Color::new(0, 0, 0), Color::new(255, 255, 255), c);
-        }
-
-        rael.render(None).await?;
+        rael.render_blocking(None)?;
     }
 }
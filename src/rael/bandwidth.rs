@@ -0,0 +1,139 @@
+use std::time::Duration;
+
+// writes smaller than this say nothing about the link
+const MIN_SAMPLE: usize = 1024;
+// a write that took less than this didn't have to wait for the link
+const BLOCKED: Duration = Duration::from_millis(2);
+// over this, the link is as good as unlimited
+const UNLIMITED: f64 = 100_000_000.0;
+// every frame paints at least this much, so the screen always gets there
+const MIN_BUDGET: usize = 2048;
+
+/// Keeps frames small enough for the link to the terminal, so a slow SSH session or serial
+/// line shows the changes a bit later instead of piling up seconds of output.
+///
+/// When a frame goes over budget, the remaining rows are left for the next frames, which
+/// start where the previous one stopped, so the screen still ends up right.
+#[derive(Debug, Clone)]
+pub struct Bandwidth {
+    /// Budgets frames from how fast the terminal takes the output
+    pub adaptive: bool,
+    /// Bytes per frame, wins over the measured budget
+    pub budget: Option<usize>,
+    throughput: Option<f64>,
+}
+
+impl Default for Bandwidth {
+    fn default() -> Self {
+        Self {
+            adaptive: true,
+            budget: None,
+            throughput: None,
+        }
+    }
+}
+
+impl Bandwidth {
+    /// Measured bytes per second, `None` while writing to the terminal never had to wait
+    pub fn throughput(&self) -> Option<f64> {
+        self.throughput
+    }
+
+    pub(crate) fn measure(&mut self, bytes: usize, elapsed: Duration) {
+        if bytes < MIN_SAMPLE {
+            return;
+        }
+        if elapsed < BLOCKED {
+            // the link kept up, see if it can take more
+            self.throughput = self.throughput.map(|t| t * 1.25).filter(|t| *t < UNLIMITED);
+            return;
+        }
+        let sample = bytes as f64 / elapsed.as_secs_f64();
+        self.throughput = Some(match self.throughput {
+            Some(t) => t * 0.7 + sample * 0.3,
+            None => sample,
+        });
+    }
+
+    /// How many bytes the frame can take, for a frame coming `frame_time` after the last one
+    pub(crate) fn frame_budget(&self, frame_time: Duration) -> Option<usize> {
+        let measured = self.throughput.filter(|_| self.adaptive).map(|t| {
+            let frame_time =
+                frame_time.clamp(Duration::from_millis(16), Duration::from_millis(250));
+            (t * frame_time.as_secs_f64()) as usize
+        });
+        self.budget.or(measured).map(|b| b.max(MIN_BUDGET))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Bandwidth, MIN_BUDGET};
+    use crate::rael::headless::{repainted_screen, screen_after, with_headless};
+    use crate::rael::Color;
+    use std::time::Duration;
+
+    #[test]
+    fn budgeted_frames_get_to_the_same_screen() {
+        let (frames, first, screen, repainted) = with_headless(60, 20, |rael, output| {
+            rael.bandwidth.budget = Some(MIN_BUDGET);
+            // every cell its own color, way over the budget
+            for y in 0..40 {
+                for x in 0..60 {
+                    rael.set_pixel(x, y, 0, Color::new(x as u8 * 4, y as u8 * 6, 128));
+                }
+            }
+            let mut bytes = Vec::new();
+            let mut frames = Vec::new();
+            for _ in 0..30 {
+                rael.render_blocking(None).unwrap();
+                bytes.extend_from_slice(&output.take());
+                frames.push(*rael.stats.last().unwrap());
+                if frames.last().unwrap().deferred_rows == 0 {
+                    break;
+                }
+            }
+            let screen = screen_after(rael, &bytes);
+            rael.bandwidth.budget = None;
+            (
+                frames.len(),
+                frames[0],
+                screen,
+                repainted_screen(rael, &output),
+            )
+        });
+        assert!(first.deferred_rows > 0);
+        // a row can go over, but not much more than that
+        assert!(
+            first.bytes_written < MIN_BUDGET * 2,
+            "{}",
+            first.bytes_written
+        );
+        // at least a row per frame
+        assert!(frames > 1 && frames <= 20, "{frames}");
+        assert_eq!(screen, repainted);
+    }
+
+    #[test]
+    fn budget_follows_the_measured_link() {
+        let mut bandwidth = Bandwidth::default();
+        let frame = Duration::from_millis(100);
+        assert_eq!(bandwidth.frame_budget(frame), None);
+
+        // too small to say anything
+        bandwidth.measure(100, Duration::from_secs(1));
+        assert_eq!(bandwidth.throughput(), None);
+
+        bandwidth.measure(100_000, Duration::from_secs(1));
+        assert_eq!(bandwidth.frame_budget(frame), Some(10_000));
+        // a write that didn't wait lets it try more
+        bandwidth.measure(10_000, Duration::ZERO);
+        assert_eq!(bandwidth.frame_budget(frame), Some(12_500));
+
+        bandwidth.budget = Some(10);
+        assert_eq!(bandwidth.frame_budget(frame), Some(MIN_BUDGET));
+        bandwidth.budget = None;
+        bandwidth.adaptive = false;
+        assert_eq!(bandwidth.frame_budget(frame), None);
+    }
+}
@@ -0,0 +1,252 @@
+use crate::rael::cell::queue_cell;
+use crate::rael::{Rael, RaelError};
+use crossterm::style::ResetColor;
+use crossterm::terminal::{Clear, ClearType};
+use crossterm::{cursor, queue};
+use std::io::{self, Write};
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex, Weak};
+use std::thread;
+
+// frames a spectator can lag behind before it gets dropped frames and a keyframe
+const BACKLOG: usize = 32;
+
+struct Subscriber {
+    tx: SyncSender<Arc<[u8]>>,
+    // got a keyframe since joining or since it lost frames
+    synced: bool,
+}
+
+/// Mirrors what Rael prints to read-only spectators on a socket, `rael-watch` shows it.
+///
+/// Spectators that join late, or can't keep up, get a keyframe of the whole screen before
+/// the next frames. The player never waits on them.
+pub struct Broadcast {
+    subscribers: Arc<Mutex<Vec<Subscriber>>>,
+    // the unix socket file, removed on drop
+    socket_path: Option<PathBuf>,
+}
+
+impl Broadcast {
+    /// Listens on `addr`: `unix:PATH` for a unix socket, anything else is a TCP address.
+    pub fn bind(addr: &str) -> Result<Self, RaelError> {
+        let subscribers = Arc::new(Mutex::new(Vec::new()));
+        let weak = Arc::downgrade(&subscribers);
+        let socket_path = match addr.strip_prefix("unix:") {
+            #[cfg(unix)]
+            Some(path) => {
+                use std::os::unix::net::UnixListener;
+                // a socket left over by a previous run would fail the bind
+                let _ = std::fs::remove_file(path);
+                let listener = UnixListener::bind(path)?;
+                spawn_listener(move || listener.accept().map(|(s, _)| s), weak)?;
+                Some(PathBuf::from(path))
+            }
+            #[cfg(not(unix))]
+            Some(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "unix sockets are not available here",
+                )
+                .into());
+            }
+            None => {
+                let listener = TcpListener::bind(addr)?;
+                spawn_listener(move || listener.accept().map(|(s, _)| s), weak)?;
+                None
+            }
+        };
+        Ok(Self {
+            subscribers,
+            socket_path,
+        })
+    }
+
+    /// How many spectators are watching
+    pub fn spectators(&self) -> usize {
+        self.subscribers.lock().map_or(0, |s| s.len())
+    }
+
+    fn wants_keyframe(&self) -> bool {
+        self.subscribers
+            .lock()
+            .is_ok_and(|s| s.iter().any(|s| !s.synced))
+    }
+
+    // to everyone that's synced, or to everyone that isn't
+    fn send(&self, bytes: &[u8], keyframe: bool) {
+        let Ok(mut subscribers) = self.subscribers.lock() else {
+            return;
+        };
+        let bytes: Arc<[u8]> = bytes.into();
+        subscribers.retain_mut(|subscriber| {
+            if subscriber.synced == keyframe {
+                return true;
+            }
+            match subscriber.tx.try_send(bytes.clone()) {
+                Ok(()) => {
+                    subscriber.synced = true;
+                    true
+                }
+                Err(TrySendError::Full(_)) => {
+                    subscriber.synced = false;
+                    true
+                }
+                Err(TrySendError::Disconnected(_)) => false,
+            }
+        });
+    }
+}
+
+impl Drop for Broadcast {
+    fn drop(&mut self) {
+        if let Some(path) = &self.socket_path {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+// accepts spectators until the broadcast is gone, each one gets a thread writing its frames
+fn spawn_listener<S, A>(mut accept: A, subscribers: Weak<Mutex<Vec<Subscriber>>>) -> io::Result<()>
+where
+    S: Write + Send + 'static,
+    A: FnMut() -> io::Result<S> + Send + 'static,
+{
+    thread::Builder::new()
+        .name("rael-broadcast".into())
+        .spawn(move || loop {
+            let stream = accept();
+            let Some(subscribers) = subscribers.upgrade() else {
+                return;
+            };
+            let Ok(mut stream) = stream else {
+                continue;
+            };
+            let (tx, rx) = mpsc::sync_channel::<Arc<[u8]>>(BACKLOG);
+            let spawned = thread::Builder::new()
+                .name("rael-spectator".into())
+                .spawn(move || {
+                    for bytes in rx {
+                        if stream
+                            .write_all(&bytes)
+                            .and_then(|_| stream.flush())
+                            .is_err()
+                        {
+                            return;
+                        }
+                    }
+                });
+            if spawned.is_ok()
+                && let Ok(mut subscribers) = subscribers.lock()
+            {
+                subscribers.push(Subscriber { tx, synced: false });
+            }
+        })?;
+    Ok(())
+}
+
+impl Rael {
+    /// Starts mirroring every frame to spectators on `addr`, see [`Broadcast::bind`].
+    /// Watch with `rael-watch ADDR`.
+    pub fn broadcast(&mut self, addr: &str) -> Result<(), RaelError> {
+        self.broadcast = Some(Broadcast::bind(addr)?);
+        Ok(())
+    }
+
+    /// Disconnects the spectators and stops listening
+    pub fn stop_broadcast(&mut self) {
+        self.broadcast = None;
+    }
+
+    // the frame to spectators that are already watching
+    pub(crate) fn broadcast_frame(&self) {
+        if let Some(broadcast) = &self.broadcast {
+            broadcast.send(&self.frame, false);
+        }
+    }
+
+    // spectators that just joined or lost frames get the whole screen
+    pub(crate) fn sync_spectators(&self, deltarune: f32) -> io::Result<()> {
+        if let Some(broadcast) = &self.broadcast
+            && broadcast.wants_keyframe()
+        {
+            broadcast.send(&self.keyframe(deltarune)?, true);
+        }
+        Ok(())
+    }
+
+    // what the terminal shows right now, printed from scratch
+    fn keyframe(&self, deltarune: f32) -> io::Result<Vec<u8>> {
+        let origin = self.inline.unwrap_or(0);
+        let mut out = Vec::new();
+        queue!(out, ResetColor, Clear(ClearType::All), cursor::Hide)?;
+        for row in 0..self.height as usize / 2 {
+            queue!(out, cursor::MoveTo(0, origin + row as u16))?;
+            for x in 0..self.widht as usize {
+                let glyph = self.old_chars[row][x];
+                if glyph.is_continuation() {
+                    continue;
+                }
+                // cells that were never painted don't have a color yet, show the frame there
+                let pixel = |y: usize| match self.old[y][x] {
+                    index if self.colors.contains_left(&index) => index,
+                    _ => self.pixels[y][x],
+                };
+                let paint = self.paint(pixel(row * 2), pixel(row * 2 + 1), glyph, deltarune);
+                queue_cell(&mut out, &self.graphemes, &paint)?;
+            }
+        }
+        queue!(out, ResetColor)?;
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Broadcast, Subscriber};
+    use crate::rael::headless::{screen_after, with_headless};
+    use crate::rael::{Color, Rael};
+    use std::sync::mpsc;
+    use std::sync::{Arc, Mutex};
+
+    fn draw(rael: &mut Rael, step: u8) {
+        for x in 0..12 {
+            rael.set_pixel(x, x % 6, 0, Color::new(step * 40, x as u8 * 20, 200));
+        }
+        let white = Color::new(255, 255, 255);
+        rael.set_str(step as usize, 4, 1, Color::new(0, 0, 0), white, "hi 中");
+    }
+
+    #[test]
+    fn late_spectator_sees_the_same_screen() {
+        let (player, spectator) = with_headless(12, 4, |rael, output| {
+            rael.broadcast = Some(Broadcast {
+                subscribers: Arc::new(Mutex::new(Vec::new())),
+                socket_path: None,
+            });
+            draw(rael, 1);
+            rael.render_blocking(None).unwrap();
+
+            // joins once the first frame is out, like a socket accepted mid-game
+            let (tx, rx) = mpsc::sync_channel(super::BACKLOG);
+            if let Some(broadcast) = &rael.broadcast {
+                let mut subscribers = broadcast.subscribers.lock().unwrap();
+                subscribers.push(Subscriber { tx, synced: false });
+            }
+            for step in 2..5 {
+                rael.clear();
+                draw(rael, step);
+                rael.render_blocking(None).unwrap();
+            }
+
+            let watched: Vec<u8> = rx.try_iter().flat_map(|bytes| bytes.to_vec()).collect();
+            (
+                screen_after(rael, &output.take()),
+                screen_after(rael, &watched),
+            )
+        });
+        assert_eq!(spectator, player);
+    }
+}
@@ -0,0 +1,277 @@
+use crate::rael::{graphemes, Color, ImageAsset, Rael};
+use rand::Rng;
+use std::time::Duration;
+
+/// Looks at a part of a world bigger than the screen, the `*_world` draw calls go
+/// through it.
+///
+/// Everything is in world pixels, the camera position is the center of the view.
+#[derive(Debug, Clone, Default)]
+pub struct Camera {
+    pub x: f32,
+    pub y: f32,
+    /// What the camera moves to on `update`, usually the player
+    pub target: Option<(f32, f32)>,
+    /// How fast the camera catches up with the target, 0 snaps right on it.
+    /// Around 5 to 10 feels smooth
+    pub smoothing: f32,
+    /// Half the size of the box around the center where the target moves freely
+    /// without the camera following
+    pub dead_zone: (f32, f32),
+    /// `(x, y, width, height)` of the world, the view never shows what's outside of it
+    pub bounds: Option<(f32, f32, f32, f32)>,
+    shake: Option<Shake>,
+    shake_offset: (f32, f32),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Shake {
+    strength: f32,
+    duration: Duration,
+    left: Duration,
+}
+
+impl Camera {
+    pub fn new(x: f32, y: f32) -> Self {
+        Self {
+            x,
+            y,
+            ..Self::default()
+        }
+    }
+
+    pub fn follow(&mut self, x: f32, y: f32) {
+        self.target = Some((x, y));
+    }
+
+    /// Moves the camera to the target right away, no smoothing
+    pub fn snap(&mut self, view: (usize, usize)) {
+        if let Some((x, y)) = self.target {
+            self.x = x;
+            self.y = y;
+        }
+        self.clamp(view);
+    }
+
+    /// Shakes the view by up to `strength` pixels, fading out over `duration`
+    pub fn shake(&mut self, strength: f32, duration: Duration) {
+        self.shake = Some(Shake {
+            // a negative strength would make an empty range to pick the offset from
+            strength: strength.max(0.0),
+            duration,
+            left: duration,
+        });
+    }
+
+    /// Moves toward the target and advances the shake, call it once per update with the
+    /// canvas size as the view (`Rael::update_camera` does that)
+    pub fn update(&mut self, dt: Duration, view: (usize, usize), rng: &mut impl Rng) {
+        if let Some((tx, ty)) = self.target {
+            let factor = if self.smoothing <= 0.0 {
+                1.0
+            } else {
+                1.0 - (-self.smoothing * dt.as_secs_f32()).exp()
+            };
+            let goal_x = dead_zone_goal(self.x, tx, self.dead_zone.0);
+            let goal_y = dead_zone_goal(self.y, ty, self.dead_zone.1);
+            self.x += (goal_x - self.x) * factor;
+            self.y += (goal_y - self.y) * factor;
+        }
+        self.clamp(view);
+
+        self.shake_offset = (0.0, 0.0);
+        if let Some(shake) = &mut self.shake {
+            shake.left = shake.left.saturating_sub(dt);
+            if shake.left.is_zero() {
+                self.shake = None;
+            } else {
+                let amount =
+                    shake.strength * shake.left.as_secs_f32() / shake.duration.as_secs_f32();
+                self.shake_offset = (
+                    rng.random_range(-amount..=amount),
+                    rng.random_range(-amount..=amount),
+                );
+            }
+        }
+    }
+
+    fn clamp(&mut self, view: (usize, usize)) {
+        let Some((bx, by, bw, bh)) = self.bounds else {
+            return;
+        };
+        self.x = clamp_axis(self.x, bx, bw, view.0 as f32);
+        self.y = clamp_axis(self.y, by, bh, view.1 as f32);
+    }
+
+    /// The world pixel at the top left corner of the view, shake included
+    pub fn origin(&self, view: (usize, usize)) -> (isize, isize) {
+        (
+            (self.x + self.shake_offset.0 - view.0 as f32 / 2.0).round() as isize,
+            (self.y + self.shake_offset.1 - view.1 as f32 / 2.0).round() as isize,
+        )
+    }
+
+    pub fn to_screen(&self, x: isize, y: isize, view: (usize, usize)) -> (isize, isize) {
+        let (ox, oy) = self.origin(view);
+        (x - ox, y - oy)
+    }
+
+    pub fn to_world(&self, x: usize, y: usize, view: (usize, usize)) -> (isize, isize) {
+        let (ox, oy) = self.origin(view);
+        (x as isize + ox, y as isize + oy)
+    }
+}
+
+fn dead_zone_goal(position: f32, target: f32, dead_zone: f32) -> f32 {
+    let distance = target - position;
+    if distance.abs() <= dead_zone {
+        position
+    } else {
+        target - dead_zone.copysign(distance)
+    }
+}
+
+// a world smaller than the view stays centered
+fn clamp_axis(center: f32, start: f32, size: f32, view: f32) -> f32 {
+    if size <= view {
+        start + size / 2.0
+    } else {
+        center.clamp(start + view / 2.0, start + size - view / 2.0)
+    }
+}
+
+impl Rael {
+    /// `camera.update` with the canvas as the view and the engine rng for the shake
+    pub fn update_camera(&mut self, dt: Duration) {
+        let view = self.canvas_size();
+        self.camera.update(dt, view, &mut self.rng);
+    }
+
+    // world to canvas, None when it's outside of the canvas
+    fn world_to_canvas(&self, x: isize, y: isize) -> Option<(usize, usize)> {
+        let view = self.canvas_size();
+        let (sx, sy) = self.camera.to_screen(x, y, view);
+        (sx >= 0 && sy >= 0 && (sx as usize) < view.0 && (sy as usize) < view.1)
+            .then_some((sx as usize, sy as usize))
+    }
+
+    /// `set_pixel` in world coordinates, whatever the camera doesn't see is skipped
+    pub fn set_pixel_world(&mut self, x: isize, y: isize, z: u8, color: Color) {
+        if let Some((x, y)) = self.world_to_canvas(x, y) {
+            self.set_pixel(x, y, z, color);
+        }
+    }
+
+    /// `set_text` in world coordinates
+    pub fn set_text_world(&mut self, x: isize, y: isize, z: u8, bg: Color, fg: Color, c: char) {
+        if let Some((x, y)) = self.world_to_canvas(x, y) {
+            self.set_text(x, y, z, bg, fg, c);
+        }
+    }
+
+    /// `set_str` in world coordinates, the graphemes out of the view are skipped
+    pub fn set_str_world(&mut self, x: isize, y: isize, z: u8, bg: Color, fg: Color, text: &str) {
+        let mut wx = x;
+        for (grapheme, width) in graphemes(text) {
+            let last_visible = self.world_to_canvas(wx + width as isize - 1, y).is_some();
+            if let Some((cx, cy)) = self.world_to_canvas(wx, y)
+                && last_visible
+            {
+                let glyph = self.get_or_insert_grapheme(grapheme);
+                if let Err(e) = self.place_glyph(cx, cy, z, bg, fg, glyph, width) {
+                    panic!("{e}");
+                }
+            }
+            wx += width as isize;
+        }
+    }
+
+    /// `set_image` in world coordinates, `pos` is the top left corner
+    pub fn set_image_world<const W: usize, const H: usize>(
+        &mut self,
+        image: ImageAsset<W, H>,
+        pos: (isize, isize, u8),
+    ) {
+        let (ox, oy, oz) = pos;
+        for y in 0..H {
+            for x in 0..W {
+                if let Some(color) = image.colors.get(image.pixels[y][x] as usize) {
+                    self.set_pixel_world(ox + x as isize, oy + y as isize, oz, *color);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Camera;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::time::Duration;
+
+    const VIEW: (usize, usize) = (20, 10);
+    const FRAME: Duration = Duration::from_millis(100);
+
+    #[test]
+    fn target_moves_freely_inside_the_dead_zone() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut camera = Camera::new(50.0, 50.0);
+        camera.dead_zone = (5.0, 5.0);
+
+        camera.follow(54.0, 46.0);
+        camera.update(FRAME, VIEW, &mut rng);
+        assert_eq!((camera.x, camera.y), (50.0, 50.0));
+
+        // out of it, the camera stops with the target on the edge of the box
+        camera.follow(60.0, 50.0);
+        camera.update(FRAME, VIEW, &mut rng);
+        assert_eq!((camera.x, camera.y), (55.0, 50.0));
+    }
+
+    #[test]
+    fn view_stays_inside_the_bounds() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut camera = Camera::new(0.0, 0.0);
+        camera.bounds = Some((0.0, 0.0, 100.0, 6.0));
+
+        camera.follow(-30.0, 40.0);
+        camera.update(FRAME, VIEW, &mut rng);
+        // the world is shorter than the view, so it gets centered
+        assert_eq!((camera.x, camera.y), (10.0, 3.0));
+        assert_eq!(camera.origin(VIEW), (0, -2));
+
+        camera.follow(500.0, 0.0);
+        camera.snap(VIEW);
+        assert_eq!(camera.x, 90.0);
+    }
+
+    #[test]
+    fn shake_fades_out_and_ends() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut camera = Camera::new(50.0, 50.0);
+        camera.shake(4.0, FRAME * 3);
+
+        let mut shaken = false;
+        for _ in 0..2 {
+            camera.update(FRAME, VIEW, &mut rng);
+            let (ox, oy) = camera.shake_offset;
+            assert!(ox.abs() <= 4.0 && oy.abs() <= 4.0);
+            shaken |= (ox, oy) != (0.0, 0.0);
+        }
+        assert!(shaken);
+
+        camera.update(FRAME, VIEW, &mut rng);
+        assert!(camera.shake.is_none());
+        assert_eq!(camera.origin(VIEW), (40, 45));
+    }
+
+    #[test]
+    fn negative_shake_does_not_panic() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut camera = Camera::new(50.0, 50.0);
+        camera.shake(-3.0, FRAME * 2);
+        camera.update(FRAME, VIEW, &mut rng);
+        assert_eq!(camera.shake_offset, (0.0, 0.0));
+    }
+}
@@ -0,0 +1,140 @@
+use crate::rael::RaelError;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Writes everything Rael prints into an [asciicast v2] file, play it back with
+/// `asciinema play`.
+///
+/// [asciicast v2]: https://docs.asciinema.org/manual/asciicast/v2/
+pub struct CastRecorder {
+    file: BufWriter<File>,
+    start: Instant,
+    size: (u16, u16),
+}
+
+impl CastRecorder {
+    /// Creates the file and writes the header with the starting terminal size (columns, rows).
+    pub fn create(path: impl AsRef<Path>, size: (u16, u16)) -> Result<Self, RaelError> {
+        let mut file = BufWriter::new(File::create(path)?);
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        writeln!(
+            file,
+            "{{\"version\": 2, \"width\": {}, \"height\": {}, \"timestamp\": {timestamp}}}",
+            size.0, size.1
+        )?;
+        Ok(Self {
+            file,
+            start: Instant::now(),
+            size,
+        })
+    }
+
+    /// Terminal size as last recorded (columns, rows)
+    pub fn size(&self) -> (u16, u16) {
+        self.size
+    }
+
+    fn event(&mut self, code: &str, data: &str) -> io::Result<()> {
+        let time = self.start.elapsed().as_secs_f64();
+        writeln!(self.file, "[{time:.6}, \"{code}\", {}]", json_string(data))
+    }
+
+    /// Records bytes that went to the terminal
+    pub fn output(&mut self, bytes: &[u8]) -> Result<(), RaelError> {
+        if bytes.is_empty() {
+            return Ok(());
+        }
+        Ok(self.event("o", &String::from_utf8_lossy(bytes))?)
+    }
+
+    /// Records a terminal resize, does nothing if the size didn't change
+    pub fn resize(&mut self, size: (u16, u16)) -> Result<(), RaelError> {
+        if size == self.size {
+            return Ok(());
+        }
+        self.size = size;
+        Ok(self.event("r", &format!("{}x{}", size.0, size.1))?)
+    }
+
+    pub fn flush(&mut self) -> Result<(), RaelError> {
+        Ok(self.file.flush()?)
+    }
+}
+
+fn json_string(data: &str) -> String {
+    let mut out = String::with_capacity(data.len() + 2);
+    out.push('"');
+    for c in data.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 || c == '\u{7f}' => {
+                out.push_str(&format!("\\u{:04x}", c as u32));
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::json_string;
+
+    fn roundtrip(data: &str) -> String {
+        serde_json::from_str(&json_string(data)).unwrap()
+    }
+
+    #[test]
+    fn escapes_quotes_and_backslashes() {
+        assert_eq!(json_string(r#"a"b\c"#), r#""a\"b\\c""#);
+        assert_eq!(roundtrip(r#"a"b\c"#), r#"a"b\c"#);
+    }
+
+    #[test]
+    fn escapes_control_characters() {
+        let data = "\x1b[2J\x00\x07\x08\x0c\n\r\t\x1f\x7f";
+        let json = json_string(data);
+        assert!(json.chars().all(|c| !c.is_control()));
+        assert_eq!(
+            json,
+            r#""\u001b[2J\u0000\u0007\u0008\u000c\n\r\t\u001f\u007f""#
+        );
+        assert_eq!(roundtrip(data), data);
+    }
+
+    #[test]
+    fn keeps_characters_outside_the_bmp() {
+        let data = "▄日本語🐱\u{1F468}\u{200D}\u{1F469}";
+        assert_eq!(roundtrip(data), data);
+    }
+
+    #[cfg(feature = "input")]
+    #[test]
+    fn records_resizes_the_game_already_took() {
+        use crate::rael::headless::with_headless;
+
+        let path = std::env::temp_dir().join(format!("rael-resize-{}.cast", std::process::id()));
+        let cast = with_headless(20, 10, {
+            let path = path.clone();
+            move |rael, _| {
+                rael.record_cast(&path).unwrap();
+                rael.inputs.push(crossterm::event::Event::Resize(30, 12));
+                rael.inputs.take_snapshot();
+                rael.render_blocking(None).unwrap();
+                rael.stop_cast().unwrap();
+                std::fs::read_to_string(&path).unwrap()
+            }
+        });
+        let _ = std::fs::remove_file(&path);
+        assert!(cast.lines().any(|line| line.ends_with(r#""r", "30x12"]"#)));
+    }
+}
@@ -0,0 +1,194 @@
+use bimap::BiMap;
+use crossterm::queue;
+use crossterm::style::{Color as CrosstermColor, Print, SetBackgroundColor, SetForegroundColor};
+use std::io::{self, Write};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use crate::rael::Color;
+
+/// What sits in one cell of the text layer.
+///
+/// Plain chars are stored as-is, grapheme clusters made of more than one char (combining
+/// accents, emoji sequences...) are interned in `Rael::graphemes` and stored by index, the
+/// same way pixels store an index into `Rael::colors`.
+/// A wide glyph (CJK, most emoji) takes two cells, the second one is a [`Glyph::CONTINUATION`].
+#[derive(Debug, PartialEq, Copy, Clone, Eq, Hash)]
+pub struct Glyph(u32);
+
+impl Glyph {
+    /// An empty cell, rendered as half blocks
+    pub const BLANK: Glyph = Glyph(' ' as u32);
+    /// Right half of a wide glyph, never printed on its own
+    pub const CONTINUATION: Glyph = Glyph(u32::MAX);
+
+    // anything above the last unicode scalar value is a grapheme index
+    const CLUSTER_BASE: u32 = char::MAX as u32 + 1;
+
+    pub fn from_char(c: char) -> Self {
+        Glyph(c as u32)
+    }
+
+    pub(crate) fn from_cluster(index: u32) -> Self {
+        Glyph(Self::CLUSTER_BASE + index)
+    }
+
+    /// The char stored in this cell, `None` for clusters and continuation cells
+    pub fn as_char(self) -> Option<char> {
+        if self.0 < Self::CLUSTER_BASE {
+            char::from_u32(self.0)
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn cluster_index(self) -> Option<u32> {
+        if self.0 >= Self::CLUSTER_BASE && self != Self::CONTINUATION {
+            Some(self.0 - Self::CLUSTER_BASE)
+        } else {
+            None
+        }
+    }
+
+    pub fn is_continuation(self) -> bool {
+        self == Self::CONTINUATION
+    }
+}
+
+/// How many cells a grapheme takes in the terminal, either 1 or 2.
+///
+/// Zero width stuff (a lone combining accent for example) still gets its own cell,
+/// otherwise the cursor would go out of sync with the grid.
+pub fn grapheme_width(grapheme: &str) -> usize {
+    grapheme.width().clamp(1, 2)
+}
+
+/// Splits a string into grapheme clusters with their display width.
+pub fn graphemes(text: &str) -> impl Iterator<Item = (&str, usize)> {
+    text.graphemes(true).map(|g| (g, grapheme_width(g)))
+}
+
+/// A cell ready to be printed: colors plus what to print.
+///
+/// Empty cells with two different halves print a `▄` with the bottom color as `fg`,
+/// `fg` is `None` when only the background shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct CellPaint {
+    pub fg: Option<Color>,
+    pub bg: Color,
+    pub glyph: Glyph,
+}
+
+fn rgb(color: Color) -> CrosstermColor {
+    CrosstermColor::Rgb {
+        r: color.r,
+        g: color.g,
+        b: color.b,
+    }
+}
+
+/// Queues the escape sequences that draw one cell at the cursor position.
+pub(crate) fn queue_cell(
+    out: &mut impl Write,
+    graphemes: &BiMap<u32, String>,
+    paint: &CellPaint,
+) -> io::Result<()> {
+    if let Some(fg) = paint.fg {
+        queue!(out, SetForegroundColor(rgb(fg)))?;
+    }
+    queue!(out, SetBackgroundColor(rgb(paint.bg)))?;
+    match paint.glyph.as_char() {
+        Some(c) => queue!(out, Print(c)),
+        None => {
+            let cluster = paint
+                .glyph
+                .cluster_index()
+                .and_then(|i| graphemes.get_by_left(&i))
+                .map_or(" ", |g| g.as_str());
+            queue!(out, Print(cluster))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rael::headless::with_headless;
+    use crate::rael::{Color, Glyph};
+
+    const BG: Color = Color { r: 0, g: 0, b: 80 };
+    const FG: Color = Color {
+        r: 255,
+        g: 255,
+        b: 255,
+    };
+
+    fn glyph(c: char) -> Glyph {
+        Glyph::from_char(c)
+    }
+
+    #[test]
+    fn wide_glyph_takes_a_continuation_cell() {
+        let row = with_headless(6, 2, |rael, _| {
+            assert_eq!(rael.set_str(0, 0, 0, BG, FG, "中a"), 3);
+            rael.chars[0]
+        });
+        assert_eq!(
+            row[..4],
+            [glyph('中'), Glyph::CONTINUATION, glyph('a'), Glyph::BLANK]
+        );
+    }
+
+    #[test]
+    fn overwriting_half_of_a_wide_glyph_blanks_the_other_half() {
+        let (right, left) = with_headless(6, 2, |rael, _| {
+            rael.set_str(0, 0, 0, BG, FG, "中");
+            rael.set_text(1, 0, 0, BG, FG, 'a');
+            let right = rael.chars[0];
+            rael.set_str(2, 0, 0, BG, FG, "中");
+            rael.set_text(2, 0, 0, BG, FG, 'b');
+            (right, rael.chars[0])
+        });
+        assert_eq!(right[..2], [Glyph::BLANK, glyph('a')]);
+        assert_eq!(left[2..4], [glyph('b'), Glyph::BLANK]);
+    }
+
+    #[test]
+    fn wide_glyph_in_the_last_column_is_not_printed() {
+        let (row, screen) = with_headless(5, 2, |rael, output| {
+            rael.set_str(4, 0, 0, BG, FG, "中");
+            rael.render_blocking(None).unwrap();
+            let mut terminal = vt100::Parser::new(2, 5, 0);
+            terminal.process(&output.take());
+            (rael.chars[0], terminal.screen().contents())
+        });
+        assert_eq!(row[4], Glyph::BLANK);
+        // a blank with two colors is a half block, and nothing wrapped onto the next row
+        assert!(!screen.contains('中'));
+        assert_eq!(screen.trim_end(), "    ▄");
+    }
+
+    #[test]
+    fn render_only_prints_what_changed() {
+        let (first, second, screen) = with_headless(8, 2, |rael, output| {
+            rael.set_str(0, 0, 0, BG, FG, "ab中");
+            rael.render_blocking(None).unwrap();
+            let first = output.take();
+            rael.set_text(1, 0, 0, BG, FG, 'x');
+            rael.render_blocking(None).unwrap();
+            let second = output.take();
+
+            let mut terminal = vt100::Parser::new(2, 8, 0);
+            terminal.process(&first);
+            terminal.process(&second);
+            (first, second, terminal.screen().contents())
+        });
+        let (first, second) = (
+            String::from_utf8_lossy(&first),
+            String::from_utf8_lossy(&second),
+        );
+        assert!(first.contains('a') && first.contains('中'));
+        assert!(second.contains('x'));
+        assert!(!second.contains('a') && !second.contains('中'));
+        assert!(screen.starts_with("ax中"));
+    }
+}
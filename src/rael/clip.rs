@@ -0,0 +1,184 @@
+use crate::rael::screenshot::FrameImage;
+use crate::rael::{Rael, RaelError};
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Animated image formats a clip can be saved as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClipFormat {
+    Gif,
+    /// Animated PNG, no palette limit unlike GIFs
+    Apng,
+}
+
+impl ClipFormat {
+    /// Picks the format from a file extension, `.gif` or `.png`/`.apng`
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "gif" => Some(ClipFormat::Gif),
+            "png" | "apng" => Some(ClipFormat::Apng),
+            _ => None,
+        }
+    }
+}
+
+struct ClipFrame {
+    rgb: Vec<u8>,
+    delay: Duration,
+}
+
+/// Keeps every rendered frame in memory and encodes them into an animation when finished.
+///
+/// Frames are captured at pixel resolution like screenshots, with the time they actually
+/// stayed on screen, identical frames in a row get merged into one.
+pub struct ClipRecorder {
+    path: PathBuf,
+    format: ClipFormat,
+    /// Region of the framebuffer being recorded (x, y, width, height)
+    pub region: (usize, usize, usize, usize),
+    frames: Vec<ClipFrame>,
+    last_capture: Option<Instant>,
+}
+
+impl ClipRecorder {
+    pub fn new(
+        path: impl AsRef<Path>,
+        region: (usize, usize, usize, usize),
+    ) -> Result<Self, RaelError> {
+        let path = path.as_ref().to_path_buf();
+        let format = ClipFormat::from_path(&path).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "clips can only be saved as .gif or .png/.apng",
+            )
+        })?;
+        Ok(Self {
+            path,
+            format,
+            region,
+            frames: Vec::new(),
+            last_capture: None,
+        })
+    }
+
+    pub(crate) fn capture(&mut self, image: FrameImage) {
+        let now = Instant::now();
+        if let (Some(last), Some(frame)) = (self.last_capture, self.frames.last_mut()) {
+            frame.delay += now - last;
+        }
+        self.last_capture = Some(now);
+
+        let (_, _, w, h) = self.region;
+        let rgb = fit(&image, w, h);
+        match self.frames.last() {
+            Some(frame) if frame.rgb == rgb => {}
+            _ => self.frames.push(ClipFrame {
+                rgb,
+                delay: Duration::ZERO,
+            }),
+        }
+    }
+
+    /// How many distinct frames were captured so far
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Encodes the captured frames and writes the file
+    pub fn finish(mut self) -> Result<(), RaelError> {
+        if let (Some(last), Some(frame)) = (self.last_capture, self.frames.last_mut()) {
+            frame.delay += last.elapsed();
+        }
+        if self.frames.is_empty() {
+            return Err(io::Error::other("no frame was rendered while recording the clip").into());
+        }
+        let out = BufWriter::new(File::create(&self.path)?);
+        match self.format {
+            ClipFormat::Gif => self.write_gif(out)?,
+            ClipFormat::Apng => self.write_apng(out)?,
+        }
+        Ok(())
+    }
+
+    fn write_gif(&self, out: BufWriter<File>) -> io::Result<()> {
+        let (_, _, w, h) = self.region;
+        let gif_err = |e: gif::EncodingError| io::Error::other(e.to_string());
+        let mut encoder = gif::Encoder::new(out, w as u16, h as u16, &[]).map_err(gif_err)?;
+        encoder.set_repeat(gif::Repeat::Infinite).map_err(gif_err)?;
+        for frame in &self.frames {
+            let mut gif_frame = gif::Frame::from_rgb_speed(w as u16, h as u16, &frame.rgb, 10);
+            // gif delays are in hundredths of a second
+            gif_frame.delay = (frame.delay.as_millis() / 10).clamp(1, u16::MAX as u128) as u16;
+            encoder.write_frame(&gif_frame).map_err(gif_err)?;
+        }
+        Ok(())
+    }
+
+    fn write_apng(&self, out: BufWriter<File>) -> io::Result<()> {
+        let (_, _, w, h) = self.region;
+        let mut encoder = png::Encoder::new(out, w as u32, h as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_animated(self.frames.len() as u32, 0)?;
+        let mut writer = encoder.write_header()?;
+        for frame in &self.frames {
+            let millis = frame.delay.as_millis().clamp(1, u16::MAX as u128) as u16;
+            writer.set_frame_delay(millis, 1000)?;
+            writer.write_image_data(&frame.rgb)?;
+        }
+        writer.finish()?;
+        Ok(())
+    }
+}
+
+// the screen can shrink while recording, every frame still needs the same size
+fn fit(image: &FrameImage, w: usize, h: usize) -> Vec<u8> {
+    if image.width == w && image.height == h {
+        return image.rgb.clone();
+    }
+    let mut rgb = vec![0; w * h * 3];
+    for y in 0..h.min(image.height) {
+        let len = w.min(image.width) * 3;
+        let src = y * image.width * 3;
+        rgb[y * w * 3..y * w * 3 + len].copy_from_slice(&image.rgb[src..src + len]);
+    }
+    rgb
+}
+
+impl Rael {
+    /// Starts capturing rendered frames for an animated GIF (`.gif`) or APNG (`.png`),
+    /// `crop` is the (x, y, width, height) in pixels to keep, the whole screen when `None`.
+    pub fn record_clip(
+        &mut self,
+        path: impl AsRef<Path>,
+        crop: Option<(usize, usize, usize, usize)>,
+    ) -> Result<(), RaelError> {
+        let (w, h) = (self.widht as usize, self.height as usize);
+        let (x, y, cw, ch) = crop.unwrap_or((0, 0, w, h));
+        let region = (
+            x,
+            y,
+            cw.min(w.saturating_sub(x)),
+            ch.min(h.saturating_sub(y)),
+        );
+        if region.2 == 0 || region.3 == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the clip region is outside of the screen",
+            )
+            .into());
+        }
+        self.clip = Some(ClipRecorder::new(path, region)?);
+        Ok(())
+    }
+
+    /// Stops capturing and encodes the clip, this can take a moment for long ones
+    pub fn finish_clip(&mut self) -> Result<(), RaelError> {
+        match self.clip.take() {
+            Some(clip) => clip.finish(),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,400 @@
+#[cfg(feature = "async")]
+use futures::future::select;
+#[cfg(feature = "async")]
+use futures_timer::Delay;
+#[cfg(feature = "async")]
+use std::future::Future;
+#[cfg(feature = "async")]
+use std::pin::{pin, Pin};
+use std::sync::{Arc, Mutex};
+#[cfg(feature = "async")]
+use std::task::{Context, Poll};
+use std::task::{Wake, Waker};
+use std::thread::{self, Thread};
+use std::time::{Duration, Instant};
+
+/// How the game time moves forward.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClockMode {
+    /// Follows the wall clock, times the time scale
+    Real,
+    /// Only moves when [`Clock::advance`] is called
+    Manual,
+    /// Every sleep finishes right away and moves the time by its duration,
+    /// a 20 seconds cutscene plays in no time but sees the exact same timings
+    FastForward,
+}
+
+struct ClockState {
+    mode: ClockMode,
+    paused: bool,
+    scale: f64,
+    /// game time at `anchor`
+    elapsed: Duration,
+    anchor: Instant,
+    /// bumped on every change, sleepers wake up to check their deadline again
+    generation: u64,
+    wakers: Vec<Waker>,
+}
+
+impl ClockState {
+    fn now(&self) -> Duration {
+        if self.mode == ClockMode::Real && !self.paused {
+            self.elapsed + self.anchor.elapsed().mul_f64(self.scale)
+        } else {
+            self.elapsed
+        }
+    }
+
+    // called before any change, so the time measured so far uses the old settings
+    fn settle(&mut self) {
+        self.elapsed = self.now();
+        self.anchor = Instant::now();
+    }
+
+    fn changed(&mut self) {
+        self.generation += 1;
+        for waker in self.wakers.drain(..) {
+            waker.wake();
+        }
+    }
+}
+
+// what a sleeper does next
+enum Wait {
+    Done,
+    // real time left before the deadline, async sleeps also stop early when the clock
+    // changes, blocking ones check again once the time is up
+    Real(
+        Duration,
+        #[cfg_attr(not(feature = "async"), allow(dead_code))] u64,
+    ),
+    // only a change to the clock can move the time
+    Changed(u64),
+}
+
+/// The game time, every wait in a scene should go through this instead of sleeping directly.
+///
+/// It can be paused, slowed down or sped up with a time scale, stepped by hand or
+/// fast-forwarded, which makes timed stuff testable. Clones share the same time.
+#[derive(Clone)]
+pub struct Clock {
+    state: Arc<Mutex<ClockState>>,
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Self::new(ClockMode::Real)
+    }
+}
+
+impl Clock {
+    pub fn new(mode: ClockMode) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(ClockState {
+                mode,
+                paused: false,
+                scale: 1.0,
+                elapsed: Duration::ZERO,
+                anchor: Instant::now(),
+                generation: 0,
+                wakers: Vec::new(),
+            })),
+        }
+    }
+
+    fn with<T>(&self, f: impl FnOnce(&mut ClockState) -> T) -> T {
+        f(&mut self.state.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    /// Game time since the clock was created
+    pub fn now(&self) -> Duration {
+        self.with(|s| s.now())
+    }
+
+    pub fn mode(&self) -> ClockMode {
+        self.with(|s| s.mode)
+    }
+
+    pub fn set_mode(&self, mode: ClockMode) {
+        self.with(|s| {
+            s.settle();
+            s.mode = mode;
+            s.changed();
+        });
+    }
+
+    pub fn pause(&self) {
+        self.set_paused(true);
+    }
+
+    pub fn resume(&self) {
+        self.set_paused(false);
+    }
+
+    pub fn set_paused(&self, paused: bool) {
+        self.with(|s| {
+            s.settle();
+            s.paused = paused;
+            s.changed();
+        });
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.with(|s| s.paused)
+    }
+
+    /// How fast the game time goes compared to the real one, `0.5` is slow motion
+    pub fn time_scale(&self) -> f64 {
+        self.with(|s| s.scale)
+    }
+
+    pub fn set_time_scale(&self, scale: f64) {
+        self.with(|s| {
+            s.settle();
+            s.scale = scale.max(0.0);
+            s.changed();
+        });
+    }
+
+    /// Moves the game time forward by hand, works in every mode, even when paused
+    pub fn advance(&self, by: Duration) {
+        self.with(|s| {
+            s.settle();
+            s.elapsed += by;
+            s.changed();
+        });
+    }
+
+    fn next_wait(&self, deadline: Duration) -> Wait {
+        self.with(|s| {
+            let now = s.now();
+            if now >= deadline {
+                return Wait::Done;
+            }
+            match s.mode {
+                ClockMode::FastForward if !s.paused => {
+                    s.elapsed = s.elapsed.max(deadline);
+                    s.changed();
+                    Wait::Done
+                }
+                ClockMode::Real if !s.paused && s.scale > 0.0 => {
+                    Wait::Real((deadline - now).div_f64(s.scale), s.generation)
+                }
+                _ => Wait::Changed(s.generation),
+            }
+        })
+    }
+
+    // false when the clock already changed since `generation`
+    fn wake_on_change(&self, generation: u64, waker: &Waker) -> bool {
+        self.with(|s| {
+            if s.generation != generation {
+                return false;
+            }
+            if !s.wakers.iter().any(|w| w.will_wake(waker)) {
+                s.wakers.push(waker.clone());
+            }
+            true
+        })
+    }
+
+    /// Waits for `duration` of game time
+    #[cfg(feature = "async")]
+    pub async fn sleep(&self, duration: Duration) {
+        let deadline = self.now() + duration;
+        self.sleep_until(deadline).await;
+    }
+
+    /// Waits until the game time reaches `deadline`
+    #[cfg(feature = "async")]
+    pub async fn sleep_until(&self, deadline: Duration) {
+        loop {
+            let changed = |generation| Changed {
+                clock: self,
+                generation,
+            };
+            match self.next_wait(deadline) {
+                Wait::Done => return,
+                Wait::Real(real, generation) => {
+                    let _ = select(pin!(Delay::new(real)), changed(generation)).await;
+                }
+                Wait::Changed(generation) => changed(generation).await,
+            }
+        }
+    }
+
+    /// `sleep` for code that isn't async, blocks the thread
+    pub fn sleep_blocking(&self, duration: Duration) {
+        let deadline = self.now() + duration;
+        self.sleep_until_blocking(deadline);
+    }
+
+    /// `sleep_until` for code that isn't async, blocks the thread
+    pub fn sleep_until_blocking(&self, deadline: Duration) {
+        let waker = THREAD_WAKER.with(Waker::clone);
+        loop {
+            match self.next_wait(deadline) {
+                Wait::Done => break,
+                Wait::Real(real, _) => thread::park_timeout(real),
+                Wait::Changed(generation) => {
+                    if self.wake_on_change(generation, &waker) {
+                        thread::park();
+                    }
+                }
+            }
+        }
+        // still there after a spurious wake up
+        self.with(|s| s.wakers.retain(|w| !w.will_wake(&waker)));
+    }
+}
+
+thread_local! {
+    // one per thread, so a sleeper never ends up twice in the list
+    static THREAD_WAKER: Waker = Waker::from(Arc::new(Unpark(thread::current())));
+}
+
+// wakes a thread blocked in a sleep
+struct Unpark(Thread);
+
+impl Wake for Unpark {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Resolves when anything about the clock changes
+#[cfg(feature = "async")]
+struct Changed<'a> {
+    clock: &'a Clock,
+    generation: u64,
+}
+
+#[cfg(feature = "async")]
+impl Future for Changed<'_> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.clock.wake_on_change(self.generation, cx.waker()) {
+            Poll::Pending
+        } else {
+            Poll::Ready(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECOND: Duration = Duration::from_secs(1);
+
+    fn wakers(clock: &Clock) -> usize {
+        clock.with(|s| s.wakers.len())
+    }
+
+    #[test]
+    fn blocking_sleeps_leave_no_waker_behind() {
+        let clock = Clock::default();
+        for _ in 0..100 {
+            clock.sleep_blocking(Duration::from_micros(1));
+        }
+        assert_eq!(wakers(&clock), 0);
+
+        let manual = Clock::new(ClockMode::Manual);
+        let sleeper = {
+            let manual = manual.clone();
+            thread::spawn(move || manual.sleep_blocking(SECOND))
+        };
+        while wakers(&manual) == 0 {
+            thread::yield_now();
+        }
+        manual.advance(SECOND);
+        sleeper.join().unwrap();
+        assert_eq!(wakers(&manual), 0);
+    }
+
+    #[test]
+    fn manual_time_only_moves_on_advance() {
+        let clock = Clock::new(ClockMode::Manual);
+        thread::sleep(Duration::from_millis(5));
+        assert_eq!(clock.now(), Duration::ZERO);
+        clock.advance(SECOND);
+        assert_eq!(clock.now(), SECOND);
+        clock.pause();
+        clock.advance(SECOND);
+        assert_eq!(clock.now(), 2 * SECOND);
+    }
+
+    #[test]
+    fn paused_real_time_stands_still() {
+        let clock = Clock::default();
+        clock.pause();
+        let now = clock.now();
+        thread::sleep(Duration::from_millis(5));
+        assert_eq!(clock.now(), now);
+        clock.resume();
+        thread::sleep(Duration::from_millis(5));
+        assert!(clock.now() > now);
+    }
+
+    #[test]
+    fn fast_forward_sleeps_move_the_time() {
+        let clock = Clock::new(ClockMode::FastForward);
+        let started = Instant::now();
+        clock.sleep_blocking(20 * SECOND);
+        clock.sleep_until_blocking(30 * SECOND);
+        assert!(started.elapsed() < SECOND);
+        assert_eq!(clock.now(), 30 * SECOND);
+    }
+
+    #[test]
+    fn advance_releases_blocked_sleepers() {
+        let clock = Clock::new(ClockMode::Manual);
+        let sleeper = {
+            let clock = clock.clone();
+            thread::spawn(move || clock.sleep_blocking(SECOND))
+        };
+        // the sleeper registers its waker, then the advance has to let it go
+        while clock.with(|s| s.wakers.is_empty()) {
+            thread::yield_now();
+        }
+        clock.advance(SECOND / 2);
+        assert!(!sleeper.is_finished());
+        clock.advance(SECOND / 2);
+        sleeper.join().unwrap();
+        assert!(clock.with(|s| s.wakers.is_empty()));
+    }
+
+    #[test]
+    fn resume_releases_paused_sleepers() {
+        let clock = Clock::new(ClockMode::FastForward);
+        clock.pause();
+        let sleeper = {
+            let clock = clock.clone();
+            thread::spawn(move || clock.sleep_blocking(SECOND))
+        };
+        while clock.with(|s| s.wakers.is_empty()) {
+            thread::yield_now();
+        }
+        assert_eq!(clock.now(), Duration::ZERO);
+        clock.resume();
+        sleeper.join().unwrap();
+        assert_eq!(clock.now(), SECOND);
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn advance_wakes_async_sleepers() {
+        let clock = Clock::new(ClockMode::Manual);
+        let sleeper = {
+            let clock = clock.clone();
+            thread::spawn(move || futures::executor::block_on(clock.sleep(SECOND)))
+        };
+        while clock.with(|s| s.wakers.is_empty()) {
+            thread::yield_now();
+        }
+        clock.advance(SECOND);
+        sleeper.join().unwrap();
+    }
+}
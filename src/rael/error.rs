@@ -0,0 +1,55 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+/// Everything that can go wrong in Rael.
+#[derive(Debug)]
+pub enum RaelError {
+    /// The terminal lacks something Rael needs, like the kitty keyboard protocol
+    Unsupported(&'static str),
+    /// Talking to the terminal or a file failed
+    Io(io::Error),
+    /// A checked draw call landed outside of the screen, in pixel coordinates
+    OutOfBounds { x: usize, y: usize },
+    /// The frame uses more colors than the palette can index
+    PaletteExhausted,
+}
+
+impl fmt::Display for RaelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RaelError::Unsupported(what) => write!(f, "unsupported terminal: {what}"),
+            RaelError::Io(e) => write!(f, "{e}"),
+            RaelError::OutOfBounds { x, y } => write!(f, "x={x} y={y} is out of the screen"),
+            RaelError::PaletteExhausted => {
+                write!(f, "the palette is full, clear_colors or force_clear")
+            }
+        }
+    }
+}
+
+impl Error for RaelError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RaelError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for RaelError {
+    fn from(e: io::Error) -> Self {
+        RaelError::Io(e)
+    }
+}
+
+// code that deals in io errors can still `?` a Rael call
+impl From<RaelError> for io::Error {
+    fn from(e: RaelError) -> Self {
+        match e {
+            RaelError::Io(e) => e,
+            RaelError::Unsupported(_) => io::Error::new(io::ErrorKind::Unsupported, e),
+            e => io::Error::other(e),
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use crate::rael::cell::{queue_cell, CellPaint};
+use crate::rael::{Color, Rael, RaelError};
+use crossterm::queue;
+use crossterm::style::ResetColor;
+use std::fmt::Write as _;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+impl Rael {
+    /// Cells of a row as they get printed, the right half of wide glyphs is skipped
+    /// since printing the left half already covers it.
+    fn row_paints(&self, row: usize) -> impl Iterator<Item = CellPaint> + '_ {
+        (0..self.widht as usize)
+            .filter(move |&x| !self.chars[row][x].is_continuation())
+            .map(move |x| self.cell_paint(x, row, 1.0))
+    }
+
+    /// The whole frame as ANSI art: the same escape sequences `render` would print for a full
+    /// repaint, with a line break after every row instead of cursor moves.
+    pub fn to_ansi(&self) -> Result<Vec<u8>, RaelError> {
+        let mut out = Vec::new();
+        queue!(out, ResetColor)?;
+        for row in 0..self.height as usize / 2 {
+            for paint in self.row_paints(row) {
+                queue_cell(&mut out, &self.graphemes, &paint)?;
+            }
+            queue!(out, ResetColor)?;
+            out.write_all(b"\r\n")?;
+        }
+        Ok(out)
+    }
+
+    /// Saves the frame as a `.ans` file, `cat` it in a truecolor terminal to see it again.
+    pub fn export_ansi(&self, path: impl AsRef<Path>) -> Result<(), RaelError> {
+        Ok(fs::write(path, self.to_ansi()?)?)
+    }
+
+    /// The whole frame as a standalone HTML page, every cell is a colored `<span>` so
+    /// the text stays selectable.
+    pub fn to_html(&self) -> String {
+        let mut html = String::new();
+        html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+        html.push_str("<title>Rael frame</title>\n</head>\n");
+        html.push_str("<body style=\"margin:0;background:#000\">\n");
+        html.push_str("<pre style=\"font-family:monospace;line-height:1;margin:0\">");
+
+        for row in 0..self.height as usize / 2 {
+            // neighbours with the same colors share a span, it keeps the page small
+            let mut style = String::new();
+            let mut text = String::new();
+            for paint in self.row_paints(row) {
+                let cell_style = match paint.fg {
+                    Some(fg) => format!("color:{};background:{}", hex(fg), hex(paint.bg)),
+                    None => format!("background:{}", hex(paint.bg)),
+                };
+                if cell_style != style {
+                    push_span(&mut html, &style, &text);
+                    style = cell_style;
+                    text.clear();
+                }
+                match paint.glyph.as_char() {
+                    Some(c) => text.push(c),
+                    None => text.push_str(self.glyph_str(paint.glyph)),
+                }
+            }
+            push_span(&mut html, &style, &text);
+            html.push('\n');
+        }
+
+        html.push_str("</pre>\n</body>\n</html>\n");
+        html
+    }
+
+    /// Saves the frame as a self-contained `.html` page.
+    pub fn export_html(&self, path: impl AsRef<Path>) -> Result<(), RaelError> {
+        Ok(fs::write(path, self.to_html())?)
+    }
+}
+
+fn hex(color: Color) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+}
+
+fn push_span(html: &mut String, style: &str, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+    let _ = write!(html, "<span style=\"{style}\">");
+    for c in text.chars() {
+        match c {
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            c => html.push(c),
+        }
+    }
+    html.push_str("</span>");
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rael::headless::with_headless;
+    use crate::rael::Color;
+
+    #[test]
+    fn exports_a_two_by_two_frame() {
+        let (red, green, blue) = (
+            Color::new(255, 0, 0),
+            Color::new(0, 255, 0),
+            Color::new(0, 0, 255),
+        );
+        let (html, ansi) = with_headless(2, 1, move |rael, _| {
+            rael.set_pixel(0, 0, 0, red);
+            rael.set_pixel(0, 1, 0, blue);
+            rael.set_pixel(1, 0, 0, green);
+            rael.set_pixel(1, 1, 0, green);
+            (rael.to_html(), rael.to_ansi().unwrap())
+        });
+        assert!(html.contains("<span style=\"color:#0000ff;background:#ff0000\">▄</span>"));
+        assert!(html.contains("<span style=\"background:#00ff00\"> </span>"));
+
+        // one more row for the line break after the last one
+        let mut terminal = vt100::Parser::new(2, 2, 0);
+        terminal.process(&ansi);
+        let screen = terminal.screen();
+        let (left, right) = (screen.cell(0, 0).unwrap(), screen.cell(0, 1).unwrap());
+        assert_eq!(left.contents(), "▄");
+        assert_eq!(left.fgcolor(), vt100::Color::Rgb(0, 0, 255));
+        assert_eq!(left.bgcolor(), vt100::Color::Rgb(255, 0, 0));
+        assert_eq!(right.bgcolor(), vt100::Color::Rgb(0, 255, 0));
+    }
+
+    #[test]
+    fn html_escapes_text() {
+        let html = with_headless(3, 1, |rael, _| {
+            let white = Color::new(255, 255, 255);
+            rael.set_str(0, 0, 0, white, white, "<&>");
+            rael.to_html()
+        });
+        assert!(html.contains("&lt;&amp;&gt;"));
+    }
+}
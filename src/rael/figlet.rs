@@ -12,8 +12,8 @@
 //!
 //! convert string literal using standard or specified font:
 //!
-//! ```
-//! use figlet_rs::FIGfont;
+//! ```no_run
+//! use rael::FIGfont;
 //!
 //! let standard_font = FIGfont::standard().unwrap();
 //! let figure = standard_font.convert("FIGlet");
@@ -305,7 +305,7 @@ impl FIGfont {
     ///
     /// [`fontdb`]: http://www.figlet.org/fontdb.cgi
     pub fn standard() -> Result<FIGfont, String> {
-        let contents = std::include_str!("../../src/underterm/default.flf");
+        let contents = std::include_str!("default.flf");
         FIGfont::from_content(contents)
     }
 
@@ -0,0 +1,182 @@
+use crate::rael::overlay::UnderOverlay;
+use crate::rael::{graphemes, Color, Rael};
+use std::time::{Duration, Instant};
+
+/// What Rael does while the terminal doesn't have the focus, everything is off by default.
+///
+/// Once the focus comes back the clock resumes (when it got paused here) and the whole
+/// screen gets repainted. The clock follows the focus whenever the input gets looked at,
+/// so a scene sleeping on the clock while it polls the input wakes up with the focus.
+/// Changes to `pause_clock` count from the next `render`.
+#[derive(Debug, Clone)]
+pub struct FocusPolicy {
+    /// Pauses `Rael::clock` while unfocused
+    pub pause_clock: bool,
+    /// Frames per second printed while unfocused, `render` skips the frames in between
+    pub throttle_fps: Option<f64>,
+    /// Shown in the middle of the screen while unfocused
+    pub overlay: Option<String>,
+    /// Background and text color of the overlay
+    pub overlay_colors: (Color, Color),
+    unfocused: bool,
+    last_frame: Option<Instant>,
+}
+
+impl Default for FocusPolicy {
+    fn default() -> Self {
+        Self {
+            pause_clock: false,
+            throttle_fps: None,
+            overlay: None,
+            overlay_colors: (Color::new(20, 20, 30), Color::new(255, 255, 255)),
+            unfocused: false,
+            last_frame: None,
+        }
+    }
+}
+
+impl FocusPolicy {
+    /// Pauses the clock, prints 4 frames per second and says so while unfocused
+    pub fn auto_pause() -> Self {
+        Self {
+            pause_clock: true,
+            throttle_fps: Some(4.0),
+            overlay: Some("PAUSED".into()),
+            ..Self::default()
+        }
+    }
+
+    /// Whether the terminal had the focus at the last `render`
+    pub fn is_focused(&self) -> bool {
+        !self.unfocused
+    }
+}
+
+impl Rael {
+    // follows the focus at the start of `render`, false when the frame gets skipped
+    pub(crate) fn update_focus(&mut self) -> bool {
+        // the input pauses and resumes the clock, see `Input::set_focus_clock`
+        let clock = self.focus.pause_clock.then(|| self.clock.clone());
+        self.inputs.set_focus_clock(clock);
+        let unfocused = self.inputs.focus_lost();
+        if unfocused != self.focus.unfocused {
+            self.focus.unfocused = unfocused;
+            if !unfocused {
+                self.focus.last_frame = None;
+                self.repaint();
+            }
+        }
+        if !unfocused {
+            return true;
+        }
+
+        let now = Instant::now();
+        if let (Some(fps), Some(last)) = (self.focus.throttle_fps, self.focus.last_frame)
+            && fps > 0.0
+            && now - last < Duration::from_secs_f64(1.0 / fps)
+        {
+            return false;
+        }
+        self.focus.last_frame = Some(now);
+        true
+    }
+
+    /// Draws the focus overlay as a band across the middle of the terminal, at z 255
+    pub(crate) fn draw_focus_overlay(&mut self) -> Option<UnderOverlay> {
+        if !self.focus.unfocused {
+            return None;
+        }
+        let text = self.focus.overlay.clone()?;
+        let (bg, fg) = self.focus.overlay_colors;
+        let (width, rows) = (self.widht as usize, self.height as usize / 2);
+        if rows == 0 {
+            return None;
+        }
+
+        // one row of padding around the text when there's room
+        let row = rows / 2;
+        let (first, last) = (row.saturating_sub(1), (row + 1).min(rows - 1));
+        let under = self.save_under_overlay(first..=last);
+
+        let text_width: usize = graphemes(&text).map(|(_, w)| w).sum();
+        let band = (text_width + 4).min(width);
+        let left = (width - band) / 2;
+        for y in first * 2..(last + 1) * 2 {
+            for x in left..left + band {
+                self.set_pixel(x, y, 255, bg);
+            }
+        }
+        let x = width.saturating_sub(text_width) / 2;
+        self.set_str(x, row * 2, 255, bg, fg, &text);
+        Some(under)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rael::headless::{screen_after, with_headless};
+    use crate::rael::{Color, Glyph, Rael};
+    use crossterm::event::Event;
+
+    // the cell rows showing the P of PAUSED
+    fn text_rows(rael: &Rael) -> Vec<usize> {
+        (0..rael.height as usize / 2)
+            .filter(|&row| rael.chars[row].contains(&Glyph::from_char('P')))
+            .collect()
+    }
+
+    #[test]
+    fn overlay_text_lands_in_the_band() {
+        let (during, band, after) = with_headless(40, 6, |rael, _| {
+            rael.focus.overlay = Some("PAUSED".into());
+            rael.focus.unfocused = true;
+            let bg = rael.focus.overlay_colors.0;
+            let under = rael.draw_focus_overlay().unwrap();
+            let during = text_rows(rael);
+            // the band is 10 columns wide, starting at 15
+            let band: Vec<_> = (0..6)
+                .filter(|&row| rael.get_pixel(15, row * 2) == Some(bg))
+                .collect();
+            rael.restore_under_overlay(under);
+            (during, band, text_rows(rael))
+        });
+        assert_eq!(band, vec![2, 3, 4]);
+        assert_eq!(during, vec![3]);
+        assert!(after.is_empty());
+    }
+
+    #[test]
+    fn skipped_frames_keep_their_dirty_rows() {
+        let screen = with_headless(10, 4, |rael, output| {
+            let (black, white) = (Color::new(0, 0, 0), Color::new(255, 255, 255));
+            rael.focus.throttle_fps = Some(0.001);
+            rael.set_str(0, 0, 0, black, white, "hi");
+            rael.render_blocking(None).unwrap();
+            rael.inputs.push(Event::FocusLost);
+            rael.render_blocking(None).unwrap();
+            // the text goes away in a frame the throttle skips
+            rael.clear();
+            rael.render_blocking(None).unwrap();
+            rael.clear();
+            rael.focus.last_frame = None;
+            rael.render_blocking(None).unwrap();
+            screen_after(rael, &output.take())
+        });
+        assert!(screen.iter().all(|(text, ..)| text == " "), "{screen:?}");
+    }
+
+    #[test]
+    fn clock_resumes_before_the_next_render() {
+        let (during, after) = with_headless(10, 4, |rael, _| {
+            rael.focus.pause_clock = true;
+            rael.render_blocking(None).unwrap();
+            rael.inputs.push(Event::FocusLost);
+            let during = rael.clock.is_paused();
+            // no render in between, like a scene sleeping on the clock
+            rael.inputs.push(Event::FocusGained);
+            (during, rael.clock.is_paused())
+        });
+        assert!(during);
+        assert!(!after);
+    }
+}
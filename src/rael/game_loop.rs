@@ -0,0 +1,269 @@
+use crate::rael::{Clock, ClockMode, Rael, RaelError};
+#[cfg(feature = "async")]
+use futures_timer::Delay;
+use std::ops::ControlFlow;
+use std::time::{Duration, Instant};
+
+/// What a frame has to do, handed out by [`GameLoop::next_frame`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tick {
+    /// How many fixed updates to run before rendering, 0 when the game is ahead
+    pub updates: u32,
+    /// Game time covered by one update
+    pub step: Duration,
+    /// Game time since the previous frame
+    pub delta: Duration,
+    /// How far the game time is between the last update and the next one, from 0 to 1,
+    /// useful to interpolate positions when rendering
+    pub alpha: f32,
+    /// Frames handed out so far, this one included
+    pub frame: u64,
+}
+
+/// Fixed timestep game loop: the game logic runs at a fixed rate no matter how fast
+/// the terminal renders, rendering happens once per frame and can be capped.
+///
+/// Time comes from a [`Clock`], so pausing it or changing its time scale affects the
+/// updates, while the frame rate cap always uses the real time.
+pub struct GameLoop {
+    clock: Clock,
+    step: Duration,
+    fps_cap: Option<f64>,
+    max_updates: u32,
+    accumulator: Duration,
+    last_time: Option<Duration>,
+    last_frame: Option<Instant>,
+    frame: u64,
+}
+
+impl GameLoop {
+    /// A loop running `updates_per_second` fixed updates, uncapped rendering
+    pub fn new(clock: Clock, updates_per_second: f64) -> Self {
+        Self {
+            clock,
+            step: Duration::from_secs_f64(1.0 / updates_per_second.max(1.0)),
+            fps_cap: None,
+            max_updates: 8,
+            accumulator: Duration::ZERO,
+            last_time: None,
+            last_frame: None,
+            frame: 0,
+        }
+    }
+
+    /// Limits how many frames get rendered per second, `None` renders as fast as possible
+    pub fn set_fps_cap(&mut self, fps: Option<f64>) {
+        self.fps_cap = fps.filter(|fps| *fps > 0.0);
+    }
+
+    /// When a frame took too long, at most this many updates run to catch up,
+    /// the rest of the late time is dropped so a slow terminal can't snowball
+    pub fn set_max_updates(&mut self, max_updates: u32) {
+        self.max_updates = max_updates.max(1);
+    }
+
+    pub fn step(&self) -> Duration {
+        self.step
+    }
+
+    // how long the frame rate cap wants to wait before the next frame
+    fn cap_wait(&self) -> Option<Duration> {
+        // a manual or fast-forwarded clock runs the frames as fast as the game asks
+        if let (Some(fps), Some(last)) = (self.fps_cap, self.last_frame)
+            && self.clock.mode() == ClockMode::Real
+        {
+            let budget = Duration::from_secs_f64(1.0 / fps);
+            return budget.checked_sub(last.elapsed()).filter(|d| !d.is_zero());
+        }
+        None
+    }
+
+    // a manual or fast-forwarded clock only moves when somebody waits on it (or advances
+    // it), so the frame waits for the game time of the next update
+    fn clock_wait(&mut self) -> Option<Duration> {
+        if self.clock.mode() == ClockMode::Real {
+            return None;
+        }
+        // the first frame counts from here, or the time it waited would be lost
+        self.last_time.get_or_insert_with(|| self.clock.now());
+        Some(self.step.saturating_sub(self.accumulator))
+    }
+
+    /// Waits for the frame rate cap, then tells how many updates this frame needs.
+    ///
+    /// With a manual or fast-forwarded clock it waits on the clock for the next update
+    /// instead, every frame runs exactly one.
+    #[cfg(feature = "async")]
+    pub async fn next_frame(&mut self) -> Tick {
+        if let Some(wait) = self.cap_wait() {
+            Delay::new(wait).await;
+        }
+        if let Some(wait) = self.clock_wait() {
+            self.clock.sleep(wait).await;
+        }
+        self.tick()
+    }
+
+    /// `next_frame` for code that isn't async, sleeps the thread for the cap
+    pub fn next_frame_blocking(&mut self) -> Tick {
+        if let Some(wait) = self.cap_wait() {
+            std::thread::sleep(wait);
+        }
+        if let Some(wait) = self.clock_wait() {
+            self.clock.sleep_blocking(wait);
+        }
+        self.tick()
+    }
+
+    fn tick(&mut self) -> Tick {
+        self.last_frame = Some(Instant::now());
+
+        let now = self.clock.now();
+        let delta = now - self.last_time.unwrap_or(now);
+        self.last_time = Some(now);
+
+        self.accumulator += delta;
+        let mut updates = (self.accumulator.as_nanos() / self.step.as_nanos()) as u32;
+        if updates > self.max_updates {
+            updates = self.max_updates;
+            self.accumulator = self.step * self.max_updates;
+        }
+        self.accumulator -= self.step * updates;
+        self.frame += 1;
+
+        Tick {
+            updates,
+            step: self.step,
+            delta,
+            alpha: self.accumulator.as_secs_f32() / self.step.as_secs_f32(),
+            frame: self.frame,
+        }
+    }
+
+    /// Runs the loop until `update` breaks or the process is asked to terminate: `update`
+    /// gets the fixed step as many times as needed, then `draw` gets the interpolation alpha
+    /// and the frame is rendered.
+    #[cfg(feature = "async")]
+    pub async fn run<U, D>(
+        &mut self,
+        rael: &mut Rael,
+        mut update: U,
+        mut draw: D,
+    ) -> Result<(), RaelError>
+    where
+        U: FnMut(&mut Rael, Duration) -> ControlFlow<()>,
+        D: FnMut(&mut Rael, f32),
+    {
+        while !rael.shutdown_requested() {
+            let tick = self.next_frame().await;
+            if Self::frame(rael, tick, &mut update, &mut draw)?.is_break() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// `run` for code that isn't async
+    pub fn run_blocking<U, D>(
+        &mut self,
+        rael: &mut Rael,
+        mut update: U,
+        mut draw: D,
+    ) -> Result<(), RaelError>
+    where
+        U: FnMut(&mut Rael, Duration) -> ControlFlow<()>,
+        D: FnMut(&mut Rael, f32),
+    {
+        while !rael.shutdown_requested() {
+            let tick = self.next_frame_blocking();
+            if Self::frame(rael, tick, &mut update, &mut draw)?.is_break() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    // the updates, drawing and rendering of one frame
+    fn frame<U, D>(
+        rael: &mut Rael,
+        tick: Tick,
+        update: &mut U,
+        draw: &mut D,
+    ) -> Result<ControlFlow<()>, RaelError>
+    where
+        U: FnMut(&mut Rael, Duration) -> ControlFlow<()>,
+        D: FnMut(&mut Rael, f32),
+    {
+        for _ in 0..tick.updates {
+            if update(rael, tick.step).is_break() {
+                return Ok(ControlFlow::Break(()));
+            }
+        }
+        draw(rael, tick.alpha);
+        rael.render_blocking(None)?;
+        Ok(ControlFlow::Continue(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rael::headless::with_headless;
+    use std::thread;
+
+    #[test]
+    fn fast_forward_runs_one_update_per_frame() {
+        let clock = Clock::new(ClockMode::FastForward);
+        let mut game_loop = GameLoop::new(clock.clone(), 30.0);
+        let updates: u32 = (0..1000)
+            .map(|_| game_loop.next_frame_blocking().updates)
+            .sum();
+        assert_eq!(updates, 1000);
+        assert_eq!(clock.now(), game_loop.step() * 1000);
+    }
+
+    #[test]
+    fn fast_forward_run_reaches_update() {
+        let updates = with_headless(20, 10, |rael, _| {
+            rael.clock.set_mode(ClockMode::FastForward);
+            let start = rael.clock.now();
+            let mut game_loop = GameLoop::new(rael.clock.clone(), 60.0);
+            let mut updates = 0;
+            game_loop
+                .run_blocking(
+                    rael,
+                    |_, _| {
+                        updates += 1;
+                        if updates == 120 {
+                            ControlFlow::Break(())
+                        } else {
+                            ControlFlow::Continue(())
+                        }
+                    },
+                    |_, _| {},
+                )
+                .unwrap();
+            (updates, rael.clock.now() - start)
+        });
+        assert_eq!(updates.0, 120);
+        assert_eq!(updates.1, Duration::from_secs_f64(1.0 / 60.0) * 120);
+    }
+
+    #[test]
+    fn manual_clock_waits_for_advance() {
+        let clock = Clock::new(ClockMode::Manual);
+        let mut game_loop = GameLoop::new(clock.clone(), 10.0);
+        let step = game_loop.step();
+        let advancer = thread::spawn(move || {
+            for _ in 0..3 {
+                thread::sleep(Duration::from_millis(10));
+                clock.advance(step);
+            }
+        });
+        let updates: u32 = (0..3)
+            .map(|_| game_loop.next_frame_blocking().updates)
+            .sum();
+        advancer.join().unwrap();
+        assert_eq!(updates, 3);
+    }
+}
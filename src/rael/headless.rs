@@ -0,0 +1,136 @@
+use crate::rael::Rael;
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+
+/// An in-memory terminal: everything Rael writes piles up here.
+///
+/// Clones share the same buffer, keep one around to look at what got rendered.
+#[derive(Clone, Default)]
+pub struct MemoryOutput {
+    buffer: Arc<Mutex<Vec<u8>>>,
+}
+
+impl MemoryOutput {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn with<T>(&self, f: impl FnOnce(&mut Vec<u8>) -> T) -> T {
+        f(&mut self.buffer.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    /// Bytes written so far
+    pub fn len(&self) -> usize {
+        self.with(|b| b.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Takes everything written so far, leaving the buffer empty
+    pub fn take(&self) -> Vec<u8> {
+        self.with(std::mem::take)
+    }
+}
+
+impl Write for MemoryOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.with(|b| b.extend_from_slice(buf));
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Rael {
+    /// A Rael that doesn't touch the terminal: no raw mode, no alternate screen, no terminal
+    /// input. It renders into `output` as if it was a terminal of `columns` x `rows` cells.
+    ///
+    /// Useful for tests, benchmarks and replays, a [`MemoryOutput`] or `io::sink()` makes a
+    /// good output.
+    pub fn headless(output: impl Write + Send + 'static, columns: u16, rows: u16) -> Self {
+        Self::with_output(Box::new(output), columns, rows, false)
+    }
+}
+
+// Rael is too big for the stack of the test threads
+#[cfg(test)]
+pub(crate) fn with_headless<T: Send + 'static>(
+    columns: u16,
+    rows: u16,
+    test: impl FnOnce(&mut Rael, MemoryOutput) -> T + Send + 'static,
+) -> T {
+    std::thread::Builder::new()
+        .stack_size(64 * 1024 * 1024)
+        .spawn(move || {
+            let output = MemoryOutput::new();
+            let mut rael = Rael::headless(output.clone(), columns, rows);
+            test(&mut rael, output)
+        })
+        .expect("failed to spawn the test thread")
+        .join()
+        .unwrap_or_else(|e| std::panic::resume_unwind(e))
+}
+
+// what a terminal of the same size shows once it got `bytes`, cell by cell with colors.
+// The terminal starts black, and the text color of an empty cell doesn't show
+#[cfg(test)]
+pub(crate) fn screen_after(rael: &Rael, bytes: &[u8]) -> Vec<(String, vt100::Color, vt100::Color)> {
+    let (columns, rows) = (rael.widht, rael.height / 2);
+    let mut terminal = vt100::Parser::new(rows, columns, 0);
+    terminal.process(bytes);
+    let screen = terminal.screen();
+    (0..rows)
+        .flat_map(|row| (0..columns).map(move |column| (row, column)))
+        .filter_map(|(row, column)| screen.cell(row, column))
+        .map(|cell| {
+            let blank = !cell.is_wide_continuation() && cell.contents().trim().is_empty();
+            let text = if blank { " " } else { cell.contents() };
+            let fg = if blank {
+                vt100::Color::Default
+            } else {
+                cell.fgcolor()
+            };
+            let bg = match cell.bgcolor() {
+                vt100::Color::Default => vt100::Color::Rgb(0, 0, 0),
+                bg => bg,
+            };
+            (text.to_string(), fg, bg)
+        })
+        .collect()
+}
+
+// the screen a full repaint of the current frame gives
+#[cfg(test)]
+pub(crate) fn repainted_screen(
+    rael: &mut Rael,
+    output: &MemoryOutput,
+) -> Vec<(String, vt100::Color, vt100::Color)> {
+    output.take();
+    rael.repaint();
+    rael.render_blocking(None).unwrap();
+    screen_after(rael, &output.take())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::with_headless;
+    use crate::rael::Color;
+
+    #[test]
+    fn first_frame_prints_the_first_color() {
+        let screen = with_headless(4, 2, |rael, output| {
+            // palette index 1, the first color a game registers
+            rael.set_pixel(1, 0, 0, Color::new(200, 10, 10));
+            rael.set_pixel(1, 1, 0, Color::new(200, 10, 10));
+            rael.render_blocking(None).unwrap();
+            let mut terminal = vt100::Parser::new(2, 4, 0);
+            terminal.process(&output.take());
+            terminal.screen().cell(0, 1).unwrap().bgcolor()
+        });
+        assert_eq!(screen, vt100::Color::Rgb(200, 10, 10));
+    }
+}
@@ -0,0 +1,57 @@
+use crate::rael::{Rael, RaelError};
+use crossterm::cursor::{self, Hide, MoveTo, MoveUp, Show};
+use crossterm::execute;
+use crossterm::style::{Print, ResetColor};
+use crossterm::terminal::window_size;
+use std::io::{self, Write};
+
+impl Rael {
+    /// A Rael drawing in `rows` lines right below the cursor, instead of taking the whole
+    /// screen: no alternate screen, no raw mode, no input, so it fits in a regular CLI tool
+    /// (progress bars, previews...).
+    ///
+    /// The last rendered frame stays in the scrollback once Rael is dropped, and the cursor
+    /// ends up on the line after it.
+    pub fn inline(mut stdout: impl Write + Send + 'static, rows: u16) -> Result<Self, RaelError> {
+        let win = window_size()?;
+        let rows = rows.clamp(1, win.rows.max(1));
+        // scrolls the screen when we're near the bottom, then comes back to the first line
+        stdout.write_all("\n".repeat(rows as usize).as_bytes())?;
+        execute!(stdout, MoveUp(rows), Hide)?;
+        let (_, origin) = cursor::position()?;
+
+        let mut rael = Self::with_output(Box::new(stdout), win.columns, rows, false);
+        rael.inline = Some(origin);
+        Ok(rael)
+    }
+
+    // leaves the frame where it is and gives the cursor back below it, the newline from
+    // its last row scrolls the screen when the frame ends at the bottom
+    pub(crate) fn leave_inline(&mut self, origin: u16) -> io::Result<()> {
+        let last_row = origin + (self.height / 2).saturating_sub(1);
+        execute!(
+            self.stdout,
+            ResetColor,
+            MoveTo(0, last_row),
+            Print("\r\n"),
+            Show
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rael::headless::with_headless;
+
+    #[test]
+    fn leaves_the_cursor_on_a_new_line_after_the_frame() {
+        let output = with_headless(10, 4, |rael, output| {
+            rael.inline = Some(5);
+            rael.leave_inline(5).unwrap();
+            rael.inline = None;
+            output.take()
+        });
+        // the last frame row is 5 + 4 - 1, 1-based for the terminal
+        assert!(String::from_utf8_lossy(&output).contains("\x1b[9;1H\r\n"));
+    }
+}
@@ -1,7 +1,17 @@
-use crossterm::event::{Event, KeyEvent, MouseEvent};
-use futures::StreamExt;
-use std::sync::Arc;
-use tokio::sync::Mutex;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseEvent};
+use std::collections::VecDeque;
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use crate::rael::clock::Clock;
+use crate::rael::journal::{Journal, JournalWriter};
+use crate::rael::keys::{KeyState, KeyStates};
+use crate::rael::{Rael, RaelError};
+
+// key events a snapshot holds on to when nobody takes them
+const SNAPSHOT_KEYS: usize = 256;
 
 /// Snapshot of the current input state.
 ///
@@ -11,66 +21,259 @@ use tokio::sync::Mutex;
 pub struct InputSnapshot {
     /// Last mouse event, if any
     pub mouse: Option<MouseEvent>,
-    /// Keys pressed since last snapshot
+    /// Keys pressed since last snapshot, see [`Input::key`] for what's held.
+    /// Only the latest 256 are kept when nobody takes them
     pub keys: Vec<KeyEvent>,
     /// Whether the terminal lost focus
     pub focus_lost: bool,
     /// Terminal size change (columns, rows) if resized
     pub resize: Option<(u16, u16)>,
+    /// Terminal size (columns, rows) of the last resize, unlike `resize` it's never reset
+    pub size: Option<(u16, u16)>,
+}
+
+impl InputSnapshot {
+    fn apply(&mut self, event: Event) {
+        match event {
+            Event::Mouse(mouse) => self.mouse = Some(mouse),
+            Event::Key(key) => self.keys.push(key),
+            Event::Resize(width, height) => {
+                self.resize = Some((width, height));
+                self.size = Some((width, height));
+            }
+            Event::FocusLost => self.focus_lost = true,
+            Event::FocusGained => self.focus_lost = false,
+            _ => {}
+        }
+    }
+}
+
+/// Where the events come from and where they go.
+enum Mode {
+    /// Events are applied as soon as they arrive
+    Live,
+    /// Events are applied as soon as they arrive, like live, and written to the journal
+    /// with the frame they arrived at
+    Recording {
+        writer: JournalWriter,
+        // a write that failed, `next_frame` reports it
+        error: Option<io::Error>,
+        start_frame: u64,
+    },
+    /// Terminal events are ignored, the journal plays at the frames it was recorded at
+    Replay {
+        events: VecDeque<(u64, Event)>,
+        start_frame: u64,
+    },
+}
+
+struct InputState {
+    snapshot: InputSnapshot,
+    keys: KeyStates,
+    mode: Mode,
+    frame: u64,
+    // paused while the focus is away, see `FocusPolicy::pause_clock`
+    focus_clock: Option<Clock>,
+    // the clock was running when the focus went away, so it's ours to resume
+    paused_clock: Option<Clock>,
+    focus_lost: bool,
+}
+
+// the snapshot and the key states see every event
+fn apply(snapshot: &mut InputSnapshot, keys: &mut KeyStates, event: Event) {
+    if let Event::Key(key) = event {
+        keys.push(key);
+    }
+    snapshot.apply(event);
+}
+
+impl InputState {
+    fn push(&mut self, event: Event) {
+        match &mut self.mode {
+            Mode::Live => apply(&mut self.snapshot, &mut self.keys, event),
+            Mode::Recording {
+                writer,
+                error,
+                start_frame,
+            } => {
+                if let Err(e) = writer.write(self.frame - *start_frame, &event) {
+                    error.get_or_insert(e);
+                }
+                apply(&mut self.snapshot, &mut self.keys, event);
+            }
+            Mode::Replay { .. } => {}
+        }
+        self.follow_focus();
+    }
+
+    // pauses and resumes the clock as soon as the focus change comes in, not at the next
+    // frame, a scene sleeping on the clock would never get there
+    fn follow_focus(&mut self) {
+        let lost = self.snapshot.focus_lost;
+        if lost == std::mem::replace(&mut self.focus_lost, lost) {
+            return;
+        }
+        if !lost {
+            if let Some(clock) = self.paused_clock.take() {
+                clock.resume();
+            }
+        } else if let Some(clock) = &self.focus_clock
+            && !clock.is_paused()
+        {
+            clock.pause();
+            self.paused_clock = Some(clock.clone());
+        }
+    }
+
+    // replayed events come out once their frame is reached
+    fn replay_due(&mut self) {
+        if let Mode::Replay {
+            events,
+            start_frame,
+        } = &mut self.mode
+        {
+            while events
+                .front()
+                .is_some_and(|(frame, _)| *frame <= self.frame - *start_frame)
+            {
+                if let Some((_, event)) = events.pop_front() {
+                    apply(&mut self.snapshot, &mut self.keys, event);
+                }
+            }
+        }
+        self.follow_focus();
+    }
 }
 
-/// Async terminal input handler.
+/// Terminal input handler, no async runtime needed.
 ///
-/// Handles keyboard, mouse, focus, and resize events in the background.
+/// Handles keyboard, mouse, focus, and resize events.
 /// Use `snapshot()` to read current input, or `take_snapshot()` to read and reset events.
+///
+/// Events can also be recorded into a journal and replayed later, frame by frame, see
+/// [`Input::start_recording`] and [`Input::start_replay`].
 #[derive(Clone)]
 pub struct Input {
-    state: Arc<Mutex<InputSnapshot>>,
+    state: Arc<Mutex<InputState>>,
+    // read the terminal events right before touching the state
+    polls_terminal: bool,
 }
 
 impl Input {
-    /// Create a new input handler from a stream of terminal events.
+    fn with_source(polls_terminal: bool) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(InputState {
+                snapshot: InputSnapshot::default(),
+                // Rael::new asks the terminal for key releases, other sources may not
+                keys: KeyStates::new(polls_terminal),
+                mode: Mode::Live,
+                frame: 0,
+                focus_clock: None,
+                paused_clock: None,
+                focus_lost: false,
+            })),
+            polls_terminal,
+        }
+    }
+
+    /// Reads the terminal events whenever the input gets looked at, no thread or task
+    /// involved. This is what `Rael::new` uses.
+    pub fn terminal() -> Self {
+        Self::with_source(true)
+    }
+
+    /// Only gets the events handed to [`Input::push`], for tests, headless runs and
+    /// custom event sources.
+    pub fn manual() -> Self {
+        Self::with_source(false)
+    }
+
+    /// Create a new input handler from a stream of terminal events, the stream gets
+    /// driven on its own thread so it works with any runtime.
     ///
     /// # Parameters
     /// - `events`: A futures stream of [`Event`] objects
     ///
     /// # Example
-    /// ```rust
+    /// ```rust,no_run
+    /// # use crossterm::event::EventStream;
+    /// # use rael::Input;
     /// let input = Input::new(EventStream::new());
     /// ```
+    #[cfg(feature = "async")]
     pub fn new(
         mut events: impl futures::Stream<Item = std::io::Result<Event>>
             + Send
             + 'static
             + std::marker::Unpin,
     ) -> Self {
-        let state = Arc::new(Mutex::new(InputSnapshot::default()));
-        let state_bg = state.clone();
-
-        // Spawn a background task to continuously update the input state
-        tokio::spawn(async move {
-            while let Some(Ok(event)) = events.next().await {
-                let mut s = state_bg.lock().await;
-                match event {
-                    Event::Mouse(mouse) => s.mouse = Some(mouse),
-                    Event::Key(key) => s.keys.push(key),
-                    Event::Resize(width, height) => s.resize = Some((width, height)),
-                    Event::FocusLost => s.focus_lost = true,
-                    Event::FocusGained => s.focus_lost = false,
-                    _ => {}
+        use futures::StreamExt;
+
+        let input = Self::manual();
+        let state = Arc::downgrade(&input.state);
+        // the thread ends with the stream, or with the next event once the input is gone
+        let _ = std::thread::Builder::new()
+            .name("rael-input".into())
+            .spawn(move || {
+                futures::executor::block_on(async {
+                    while let Some(Ok(event)) = events.next().await {
+                        let Some(state) = state.upgrade() else {
+                            return;
+                        };
+                        state.lock().unwrap_or_else(|e| e.into_inner()).push(event);
+                    }
+                })
+            });
+        input
+    }
+
+    /// Hands an event to the input, as if the terminal sent it
+    pub fn push(&self, event: Event) {
+        self.state
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(event);
+    }
+
+    fn lock(&self) -> MutexGuard<'_, InputState> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if self.polls_terminal {
+            // a read error means there's no terminal left to read from
+            while event::poll(Duration::ZERO).unwrap_or(false) {
+                match event::read() {
+                    Ok(event) => state.push(event),
+                    Err(_) => break,
                 }
             }
-        });
+        }
+        state.replay_due();
+        state
+    }
+
+    /// Whether the terminal lost the focus (and didn't get it back yet)
+    pub fn focus_lost(&self) -> bool {
+        self.lock().snapshot.focus_lost
+    }
 
-        Self { state }
+    // the clock to pause while unfocused, `None` leaves the clock alone
+    pub(crate) fn set_focus_clock(&self, clock: Option<Clock>) {
+        self.state
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .focus_clock = clock;
+    }
+
+    /// Terminal size (columns, rows) of the last resize event, if there was one
+    pub fn size(&self) -> Option<(u16, u16)> {
+        self.lock().snapshot.size
     }
 
     /// Get a snapshot of the current input state.
     ///
     /// This does **not reset** keys, mouse, or resize info.
     /// Useful if you just want to inspect the current state without clearing events.
-    pub async fn snapshot(&self) -> InputSnapshot {
-        self.state.lock().await.clone()
+    pub fn snapshot(&self) -> InputSnapshot {
+        self.lock().snapshot.clone()
     }
 
     /// Take a snapshot of the current input and **reset** events.
@@ -79,17 +282,20 @@ impl Input {
     /// Useful for per-frame input handling in a game loop.
     ///
     /// # Example
-    /// ```rust
-    /// let snap = input.take_snapshot().await;
+    /// ```rust,no_run
+    /// # fn example(input: rael::Input) {
+    /// let snap = input.take_snapshot();
     /// if let Some(mouse) = snap.mouse {
     ///     println!("Mouse event: {:?}", mouse);
     /// }
     /// for key in snap.keys {
     ///     println!("Key pressed: {:?}", key);
     /// }
+    /// # }
     /// ```
-    pub async fn take_snapshot(&self) -> InputSnapshot {
-        let mut s = self.state.lock().await;
+    pub fn take_snapshot(&self) -> InputSnapshot {
+        let mut state = self.lock();
+        let s = &mut state.snapshot;
         let snap = s.clone();
 
         s.mouse = None;
@@ -98,4 +304,246 @@ impl Input {
 
         snap
     }
+
+    /// Where `code` is at this frame. The edges (`pressed`, `released`, `repeats`) last
+    /// until the next [`Input::next_frame`], and an event that comes in after the game
+    /// looked shows up in the next frame instead of getting lost.
+    ///
+    /// Terminals without the kitty protocol never say a key went up. Until a release shows
+    /// up, keys count as let go once a frame goes by without a press or repeat of them.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # use crossterm::event::KeyCode;
+    /// # fn example(input: rael::Input, x: &mut i32) {
+    /// if input.is_down(KeyCode::Right) {
+    ///     *x += 1;
+    /// }
+    /// if input.just_pressed(KeyCode::Char(' ')) {
+    ///     println!("jump");
+    /// }
+    /// # }
+    /// ```
+    pub fn key(&self, code: KeyCode) -> KeyState {
+        self.lock().keys.get(code)
+    }
+
+    /// Whether `code` is held down
+    pub fn is_down(&self, code: KeyCode) -> bool {
+        self.key(code).down
+    }
+
+    /// Whether `code` went down this frame
+    pub fn just_pressed(&self, code: KeyCode) -> bool {
+        self.key(code).pressed
+    }
+
+    /// Whether `code` went up this frame
+    pub fn just_released(&self, code: KeyCode) -> bool {
+        self.key(code).released
+    }
+
+    /// How many times `code` auto-repeated this frame
+    pub fn repeats(&self, code: KeyCode) -> u32 {
+        self.key(code).repeats
+    }
+
+    /// Removes the presses of `code` from the pending keys and tells if there was any.
+    ///
+    /// Used by the engine for its own hotkeys, so the game never sees them.
+    pub fn take_key_press(&self, code: KeyCode) -> bool {
+        let mut state = self.lock();
+        let s = &mut state.snapshot;
+        let before = s.keys.len();
+        s.keys
+            .retain(|k| k.code != code || k.kind == KeyEventKind::Release);
+        let taken = s.keys.len() != before;
+        if taken {
+            state.keys.forget(code);
+        }
+        taken
+    }
+
+    /// Like [`Input::take_key_press`], but only the presses with exactly these modifiers
+    pub fn take_key_combo(&self, code: KeyCode, modifiers: KeyModifiers) -> bool {
+        let mut state = self.lock();
+        let s = &mut state.snapshot;
+        let before = s.keys.len();
+        s.keys.retain(|k| {
+            k.code != code || k.modifiers != modifiers || k.kind == KeyEventKind::Release
+        });
+        let taken = s.keys.len() != before;
+        if taken {
+            state.keys.forget(code);
+        }
+        taken
+    }
+
+    /// Tells the handler a frame went by, `Rael::render` does it for you.
+    ///
+    /// Journals count the frames: a recording writes down the frame every event came in at,
+    /// and a replay hands it out as soon as that frame starts.
+    pub fn next_frame(&self) -> Result<(), RaelError> {
+        let mut state = self.lock();
+        state.frame += 1;
+        state.keys.next_frame();
+        let keys = &mut state.snapshot.keys;
+        keys.drain(..keys.len().saturating_sub(SNAPSHOT_KEYS));
+        state.replay_due();
+        if let Mode::Recording { writer, error, .. } = &mut state.mode {
+            if let Some(e) = error.take() {
+                return Err(e.into());
+            }
+            writer.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Frames counted by [`Input::next_frame`] so far
+    pub fn frame(&self) -> u64 {
+        self.lock().frame
+    }
+
+    /// Starts writing every event into a journal file, `seed` goes into its header so the
+    /// replay can use the same random numbers.
+    pub fn start_recording(&self, path: impl AsRef<Path>, seed: u64) -> Result<(), RaelError> {
+        let writer = JournalWriter::create(path, seed)?;
+        let mut state = self.lock();
+        state.mode = Mode::Recording {
+            writer,
+            error: None,
+            start_frame: state.frame,
+        };
+        Ok(())
+    }
+
+    /// Replaces the terminal events with the ones of a journal, returns the recorded seed.
+    pub fn start_replay(&self, path: impl AsRef<Path>) -> Result<u64, RaelError> {
+        let journal = Journal::load(path)?;
+        let mut state = self.lock();
+        state.snapshot = InputSnapshot::default();
+        state.keys.clear();
+        state.mode = Mode::Replay {
+            events: journal.events,
+            start_frame: state.frame,
+        };
+        Ok(journal.seed)
+    }
+
+    /// Whether a replay is running and every event of it got applied
+    pub fn replay_finished(&self) -> bool {
+        matches!(&self.lock().mode, Mode::Replay { events, .. } if events.is_empty())
+    }
+
+    /// Stops recording or replaying and goes back to live terminal events
+    pub fn stop_journal(&self) -> Result<(), RaelError> {
+        let mut state = self.lock();
+        let mode = std::mem::replace(&mut state.mode, Mode::Live);
+        if let Mode::Recording {
+            mut writer, error, ..
+        } = mode
+        {
+            if let Some(e) = error {
+                return Err(e.into());
+            }
+            writer.flush()?;
+        }
+        Ok(())
+    }
+}
+
+impl Rael {
+    // swaps the input the constructors start with
+    pub(crate) fn with_input(mut self, inputs: Input) -> Self {
+        self.inputs = inputs;
+        self
+    }
+
+    /// Starts writing every input event into a journal, along with the frame it was seen at.
+    /// `rng` gets reseeded so the recording knows every random number that comes after.
+    pub fn record_input(&mut self, path: impl AsRef<Path>) -> Result<(), RaelError> {
+        self.reseed(rand::random());
+        self.inputs.start_recording(path, self.seed)
+    }
+
+    /// Plays back a journal written by `record_input` instead of the terminal input,
+    /// with the same seed for `rng`.
+    pub fn replay_input(&mut self, path: impl AsRef<Path>) -> Result<(), RaelError> {
+        let seed = self.inputs.start_replay(path)?;
+        self.reseed(seed);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rael::ClockMode;
+
+    fn press(c: char) -> Event {
+        Event::Key(KeyEvent::from(KeyCode::Char(c)))
+    }
+
+    #[test]
+    fn recording_sees_events_right_away_and_replays_them_at_their_frame() {
+        let path = std::env::temp_dir().join(format!("rael-journal-{}.jsonl", std::process::id()));
+        let input = Input::manual();
+        input.next_frame().unwrap();
+        input.start_recording(&path, 7).unwrap();
+        input.push(press('a'));
+        assert!(input.just_pressed(KeyCode::Char('a')));
+        input.next_frame().unwrap();
+        input.next_frame().unwrap();
+        input.push(press('b'));
+        assert!(input.just_pressed(KeyCode::Char('b')));
+        input.next_frame().unwrap();
+        input.stop_journal().unwrap();
+
+        let replay = Input::manual();
+        assert_eq!(replay.start_replay(&path).unwrap(), 7);
+        let _ = std::fs::remove_file(&path);
+        assert!(replay.just_pressed(KeyCode::Char('a')));
+        replay.next_frame().unwrap();
+        assert!(!replay.just_pressed(KeyCode::Char('b')));
+        replay.next_frame().unwrap();
+        assert!(replay.just_pressed(KeyCode::Char('b')));
+        assert!(replay.replay_finished());
+        // the terminal is ignored while replaying
+        replay.push(press('c'));
+        assert!(!replay.just_pressed(KeyCode::Char('c')));
+    }
+
+    #[test]
+    fn untaken_keys_are_capped() {
+        let input = Input::manual();
+        for _ in 0..SNAPSHOT_KEYS {
+            input.push(press('a'));
+        }
+        input.push(press('b'));
+        input.next_frame().unwrap();
+        let keys = input.snapshot().keys;
+        assert_eq!(keys.len(), SNAPSHOT_KEYS);
+        assert_eq!(keys.last().map(|k| k.code), Some(KeyCode::Char('b')));
+        // a game taking them every frame misses nothing
+        input.push(press('c'));
+        input.next_frame().unwrap();
+        assert_eq!(input.take_snapshot().keys.len(), SNAPSHOT_KEYS);
+    }
+
+    #[test]
+    fn focus_pauses_the_clock_without_a_frame() {
+        let input = Input::manual();
+        let clock = Clock::new(ClockMode::Manual);
+        input.set_focus_clock(Some(clock.clone()));
+        input.push(Event::FocusLost);
+        assert!(clock.is_paused());
+        input.push(Event::FocusGained);
+        assert!(!clock.is_paused());
+
+        // a clock the game paused itself stays paused
+        clock.pause();
+        input.push(Event::FocusLost);
+        input.push(Event::FocusGained);
+        assert!(clock.is_paused());
+    }
 }
@@ -0,0 +1,91 @@
+use crossterm::event::Event;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::time::Instant;
+
+/// First line of an input journal.
+#[derive(Serialize, Deserialize)]
+struct JournalHeader {
+    /// Seed of `Rael::rng` when the recording started
+    seed: u64,
+}
+
+/// One event of an input journal, one per line after the header.
+#[derive(Serialize, Deserialize)]
+struct JournalEntry {
+    /// Frame the event got applied at, counted from the start of the recording
+    frame: u64,
+    /// Milliseconds since the start of the recording, only there for humans
+    ms: u64,
+    event: Event,
+}
+
+fn invalid(e: serde_json::Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+/// Writes input events into a journal file (JSON lines).
+pub(crate) struct JournalWriter {
+    file: BufWriter<File>,
+    start: Instant,
+}
+
+impl JournalWriter {
+    pub fn create(path: impl AsRef<Path>, seed: u64) -> io::Result<Self> {
+        let mut file = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(&mut file, &JournalHeader { seed }).map_err(invalid)?;
+        file.write_all(b"\n")?;
+        Ok(Self {
+            file,
+            start: Instant::now(),
+        })
+    }
+
+    pub fn write(&mut self, frame: u64, event: &Event) -> io::Result<()> {
+        let entry = JournalEntry {
+            frame,
+            ms: self.start.elapsed().as_millis() as u64,
+            event: event.clone(),
+        };
+        serde_json::to_writer(&mut self.file, &entry).map_err(invalid)?;
+        self.file.write_all(b"\n")
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// A journal loaded back for replay.
+pub(crate) struct Journal {
+    pub seed: u64,
+    /// (frame, event) in the order they were recorded
+    pub events: VecDeque<(u64, Event)>,
+}
+
+impl Journal {
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut lines = BufReader::new(File::open(path)?).lines();
+        let header = lines
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "empty input journal"))??;
+        let header: JournalHeader = serde_json::from_str(&header).map_err(invalid)?;
+
+        let mut events = VecDeque::new();
+        for line in lines {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry: JournalEntry = serde_json::from_str(&line).map_err(invalid)?;
+            events.push_back((entry.frame, entry.event));
+        }
+        Ok(Self {
+            seed: header.seed,
+            events,
+        })
+    }
+}
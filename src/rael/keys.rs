@@ -0,0 +1,205 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
+use std::collections::HashMap;
+
+/// Where a key is at during the current frame, see [`Input::key`](crate::Input::key).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct KeyState {
+    /// Held down
+    pub down: bool,
+    /// Went down this frame, a tap that's already released still counts
+    pub pressed: bool,
+    /// Went up this frame
+    pub released: bool,
+    /// Auto-repeats this frame while held
+    pub repeats: u32,
+}
+
+/// Keys held and what happened to them, the edges last one frame.
+///
+/// Events only get applied when somebody looks, and the edges of the previous frame are
+/// dropped right before, so an event seen late in a frame still shows up in the next one.
+pub(crate) struct KeyStates {
+    // with whether it got pressed or repeated this frame, terminals without releases only
+    // have that to go by
+    keys: HashMap<KeyCode, (KeyState, bool)>,
+    pending: Vec<KeyEvent>,
+    // a frame went by since the edges were last looked at
+    stale: bool,
+    // terminals without the kitty protocol never say a key went up
+    reports_releases: bool,
+}
+
+impl KeyStates {
+    pub(crate) fn new(reports_releases: bool) -> Self {
+        Self {
+            keys: HashMap::new(),
+            pending: Vec::new(),
+            stale: false,
+            reports_releases,
+        }
+    }
+
+    pub(crate) fn push(&mut self, key: KeyEvent) {
+        self.pending.push(key);
+    }
+
+    pub(crate) fn next_frame(&mut self) {
+        self.stale = true;
+    }
+
+    /// The state of `code` this frame
+    pub(crate) fn get(&mut self, code: KeyCode) -> KeyState {
+        self.settle();
+        self.keys
+            .get(&code)
+            .map_or_else(KeyState::default, |(key, _)| *key)
+    }
+
+    /// Drops everything about `code`, for the keys the engine keeps for itself
+    pub(crate) fn forget(&mut self, code: KeyCode) {
+        self.pending.retain(|key| key.code != code);
+        self.keys.remove(&code);
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.keys.clear();
+        self.pending.clear();
+        self.stale = false;
+    }
+
+    fn settle(&mut self) {
+        if std::mem::take(&mut self.stale) {
+            let reports_releases = self.reports_releases;
+            self.keys.retain(|_, (key, active)| {
+                // without releases a key is let go once a frame goes by without it
+                let let_go = key.down && !reports_releases && !*active;
+                *key = KeyState {
+                    down: key.down && !let_go,
+                    released: let_go,
+                    ..KeyState::default()
+                };
+                *active = false;
+                key.down || key.released
+            });
+        }
+        for event in self.pending.drain(..) {
+            let (key, active) = self.keys.entry(event.code).or_default();
+            match event.kind {
+                // what an auto-repeat looks like without the kitty protocol
+                KeyEventKind::Press if key.down => key.repeats += 1,
+                KeyEventKind::Press => {
+                    key.down = true;
+                    key.pressed = true;
+                }
+                KeyEventKind::Repeat => {
+                    key.down = true;
+                    key.repeats += 1;
+                }
+                KeyEventKind::Release => {
+                    self.reports_releases = true;
+                    if key.down {
+                        key.down = false;
+                        key.released = true;
+                    }
+                    continue;
+                }
+            }
+            *active = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    const SPACE: KeyCode = KeyCode::Char(' ');
+
+    fn event(kind: KeyEventKind) -> KeyEvent {
+        KeyEvent::new_with_kind(SPACE, KeyModifiers::NONE, kind)
+    }
+
+    fn state(down: bool, pressed: bool, released: bool, repeats: u32) -> KeyState {
+        KeyState {
+            down,
+            pressed,
+            released,
+            repeats,
+        }
+    }
+
+    #[test]
+    fn tap_within_a_frame_is_pressed_and_released() {
+        let mut keys = KeyStates::new(true);
+        keys.push(event(KeyEventKind::Press));
+        keys.push(event(KeyEventKind::Release));
+        assert_eq!(keys.get(SPACE), state(false, true, true, 0));
+        keys.next_frame();
+        assert_eq!(keys.get(SPACE), KeyState::default());
+    }
+
+    #[test]
+    fn held_key_keeps_down_and_counts_repeats() {
+        let mut keys = KeyStates::new(true);
+        keys.push(event(KeyEventKind::Press));
+        assert_eq!(keys.get(SPACE), state(true, true, false, 0));
+        keys.next_frame();
+        keys.push(event(KeyEventKind::Repeat));
+        keys.push(event(KeyEventKind::Repeat));
+        assert_eq!(keys.get(SPACE), state(true, false, false, 2));
+        // with releases reported, a quiet frame doesn't let it go
+        keys.next_frame();
+        keys.next_frame();
+        assert_eq!(keys.get(SPACE), state(true, false, false, 0));
+        keys.push(event(KeyEventKind::Release));
+        assert_eq!(keys.get(SPACE), state(false, false, true, 0));
+    }
+
+    #[test]
+    fn legacy_keys_are_let_go_after_a_quiet_frame() {
+        let mut keys = KeyStates::new(false);
+        keys.push(event(KeyEventKind::Press));
+        assert_eq!(keys.get(SPACE), state(true, true, false, 0));
+        // auto-repeat comes as more presses
+        keys.next_frame();
+        keys.push(event(KeyEventKind::Press));
+        assert_eq!(keys.get(SPACE), state(true, false, false, 1));
+        keys.next_frame();
+        assert_eq!(keys.get(SPACE), state(true, false, false, 0));
+        keys.next_frame();
+        assert_eq!(keys.get(SPACE), state(false, false, true, 0));
+        keys.next_frame();
+        assert_eq!(keys.get(SPACE), KeyState::default());
+    }
+
+    #[test]
+    fn a_release_turns_the_legacy_guess_off() {
+        let mut keys = KeyStates::new(false);
+        keys.push(event(KeyEventKind::Release));
+        keys.push(event(KeyEventKind::Press));
+        keys.next_frame();
+        keys.next_frame();
+        assert!(keys.get(SPACE).down);
+    }
+
+    #[test]
+    fn late_events_show_up_next_frame() {
+        let mut keys = KeyStates::new(true);
+        assert_eq!(keys.get(SPACE), KeyState::default());
+        // comes in after the game looked
+        keys.push(event(KeyEventKind::Press));
+        keys.next_frame();
+        assert!(keys.get(SPACE).pressed);
+    }
+
+    #[test]
+    fn forget_drops_pending_and_applied_events() {
+        let mut keys = KeyStates::new(true);
+        keys.push(event(KeyEventKind::Press));
+        keys.get(SPACE);
+        keys.push(event(KeyEventKind::Repeat));
+        keys.forget(SPACE);
+        assert_eq!(keys.get(SPACE), KeyState::default());
+    }
+}
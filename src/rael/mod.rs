@@ -1,28 +1,87 @@
 use bimap::BiMap;
 use crossterm::cursor;
-use crossterm::event::{
-    DisableFocusChange, DisableMouseCapture, EnableMouseCapture, EventStream,
-    KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
-};
+#[cfg(feature = "input")]
+use crossterm::event::{KeyCode, KeyModifiers};
 use crossterm::queue;
-use crossterm::style::{Color as CrosstermColor, Print, SetBackgroundColor, SetForegroundColor};
-use crossterm::{
-    cursor::{Hide, Show},
-    event::EnableFocusChange,
-    execute,
-    terminal::{
-        disable_raw_mode, enable_raw_mode, supports_keyboard_enhancement, window_size,
-        BeginSynchronizedUpdate, DisableLineWrap, EnableLineWrap, EndSynchronizedUpdate,
-        EnterAlternateScreen, LeaveAlternateScreen, SetTitle,
-    },
+use crossterm::terminal::{
+    supports_keyboard_enhancement, window_size, BeginSynchronizedUpdate, EndSynchronizedUpdate,
 };
-use std::io::{self, Stdout, Write};
-
-use crate::rael::input::Input;
-
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::io::Write;
+use std::path::Path;
+#[cfg(feature = "capture")]
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+pub use crate::rael::bandwidth::Bandwidth;
+pub use crate::rael::broadcast::Broadcast;
+pub use crate::rael::camera::Camera;
+pub use crate::rael::cast::CastRecorder;
+pub use crate::rael::cell::{grapheme_width, graphemes, Glyph};
+use crate::rael::cell::{queue_cell, CellPaint};
+#[cfg(feature = "capture")]
+pub use crate::rael::clip::{ClipFormat, ClipRecorder};
+pub use crate::rael::clock::{Clock, ClockMode};
+pub use crate::rael::error::RaelError;
+#[cfg(feature = "figlet-text")]
+pub use crate::rael::figlet::{FIGcharacter, FIGfont, FIGure};
+#[cfg(feature = "input")]
+pub use crate::rael::focus::FocusPolicy;
+pub use crate::rael::game_loop::{GameLoop, Tick};
+pub use crate::rael::headless::MemoryOutput;
+#[cfg(feature = "input")]
+pub use crate::rael::input::{Input, InputSnapshot};
+#[cfg(feature = "input")]
+pub use crate::rael::keys::KeyState;
+pub use crate::rael::query::TextCell;
+#[cfg(feature = "server")]
+pub use crate::rael::server::serve;
+pub use crate::rael::stats::{FrameStats, Stats};
+pub use crate::rael::terminal::set_panic_log;
+pub use crate::rael::viewport::Scaling;
+use crate::rael::viewport::Viewport;
+
+mod bandwidth;
+mod broadcast;
+mod camera;
+mod cast;
+mod cell;
+#[cfg(feature = "capture")]
+mod clip;
+mod clock;
+mod error;
+mod export;
+#[cfg(feature = "figlet-text")]
+mod figlet;
+#[cfg(feature = "input")]
+mod focus;
+mod game_loop;
+mod headless;
+mod inline;
+#[cfg(feature = "input")]
 mod input;
-
-const MAX: usize = 512;
+#[cfg(feature = "input")]
+mod journal;
+#[cfg(feature = "input")]
+mod keys;
+mod overlay;
+mod query;
+#[cfg(feature = "capture")]
+mod screenshot;
+mod scroll;
+#[cfg(feature = "server")]
+mod server;
+mod signals;
+mod stats;
+#[cfg(feature = "server")]
+mod telnet;
+mod terminal;
+mod viewport;
+
+pub(crate) const MAX: usize = 512;
 
 #[derive(Debug, PartialEq, Copy, Clone, Eq, Hash)]
 pub struct Color {
@@ -58,93 +117,357 @@ pub struct Rael {
     pub pixels: [[u16; MAX]; MAX],
     pub z_buffer: [[u8; MAX]; MAX],
     pub colors: BiMap<u16, Color>,
-    pub stdout: Stdout,
+    pub stdout: Box<dyn Write + Send>,
     pub old: Box<[[u16; MAX]; MAX]>,
+    #[cfg(feature = "input")]
     pub inputs: Input,
-    pub chars: [[char; MAX]; MAX / 2],
-    pub old_chars: Box<[[char; MAX]; MAX / 2]>,
+    pub chars: [[Glyph; MAX]; MAX / 2],
+    pub old_chars: Box<[[Glyph; MAX]; MAX / 2]>,
+    pub graphemes: BiMap<u32, String>,
     pub dirty_rows: [u128; 2],
+    /// Pressing this key saves a screenshot in `screenshot_dir`, `None` disables it
+    #[cfg(all(feature = "input", feature = "capture"))]
+    pub screenshot_key: Option<KeyCode>,
+    #[cfg(feature = "capture")]
+    pub screenshot_dir: PathBuf,
+    /// Where the last screenshot hotkey press saved to, or why it couldn't
+    #[cfg(all(feature = "input", feature = "capture"))]
+    pub last_screenshot: Option<Result<PathBuf, RaelError>>,
+    /// Everything printed gets mirrored into this asciicast while it's set
+    pub cast: Option<CastRecorder>,
+    /// Spectators watching every frame while it's set
+    pub broadcast: Option<Broadcast>,
+    /// Every rendered frame gets captured into this animation while it's set
+    #[cfg(feature = "capture")]
+    pub clip: Option<ClipRecorder>,
+    /// Game time, scenes should wait on this rather than sleeping directly
+    pub clock: Clock,
+    /// Frame statistics, with an overlay to see them live
+    pub stats: Stats,
+    /// Where the `*_world` draw calls look from
+    pub camera: Camera,
+    /// Frames that are the previous one moved up or down get scrolled by the terminal
+    /// instead of printed again
+    pub scroll_regions: bool,
+    /// Byte budget of the frames, for slow links
+    pub bandwidth: Bandwidth,
+    // rows over the budget of the last frame, and where the next frame starts painting
+    deferred_rows: [u128; 2],
+    next_row: usize,
+    /// Pressing this key toggles the stats overlay, `None` disables it
+    #[cfg(feature = "input")]
+    pub stats_key: Option<KeyCode>,
+    /// Pressing this suspends the game like Ctrl+Z does in a shell (raw mode turns it into
+    /// a plain key press), `None` disables it
+    #[cfg(feature = "input")]
+    pub suspend_key: Option<(KeyCode, KeyModifiers)>,
+    /// What happens while the terminal doesn't have the focus, nothing by default
+    #[cfg(feature = "input")]
+    pub focus: FocusPolicy,
+    last_render: Option<Instant>,
+    /// Use this for anything random in the game, input replays reseed it
+    pub rng: StdRng,
+    seed: u64,
+    // bytes of the frame being rendered, written out all at once
+    frame: Vec<u8>,
+    // whether we changed the terminal modes and have to restore them
+    terminal: bool,
+    // top terminal row of the reserved region in inline mode
+    inline: Option<u16>,
+    // how dim the last frame was printed, captures show it the same way
+    deltarune: f32,
+    // where the logical canvas lands on the screen, when there is one
+    viewport: Option<Viewport>,
+    // a client on a socket, its terminal gets restored on drop too
+    remote: bool,
+    // set once that client hung up
+    hangup: Option<Arc<AtomicBool>>,
 }
 
 impl Rael {
-    pub fn new(mut stdout: Stdout, title: &str) -> Result<Self, io::Error> {
-        if !supports_keyboard_enhancement().unwrap() {
-            return Err(io::Error::new(
-                io::ErrorKind::Unsupported,
-                "Terminal doesn't support Kitty protocols, required for rendering",
+    pub fn new(mut stdout: impl Write + Send + 'static, title: &str) -> Result<Self, RaelError> {
+        if !supports_keyboard_enhancement()? {
+            return Err(RaelError::Unsupported(
+                "the kitty keyboard protocol is required for rendering",
             ));
         };
 
-        let _ = enable_raw_mode();
-        execute!(
-            stdout,
-            EnterAlternateScreen,
-            DisableLineWrap,
-            EnableFocusChange,
-            EnableMouseCapture,
-            SetTitle::<&str>(title),
-            Hide,
-            PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::all())
-        )?;
-
-        let win = window_size().unwrap();
-        let reader = EventStream::new();
+        terminal::install_panic_hook();
+        signals::install(title);
+        terminal::enter(&mut stdout, title)?;
+
+        let win = window_size()?;
+        let rael = Self::with_output(Box::new(stdout), win.columns, win.rows, true);
+        #[cfg(feature = "input")]
+        let rael = rael.with_input(Input::terminal());
+        Ok(rael)
+    }
+
+    // everything but the terminal setup, shared by all the constructors, the input only
+    // gets the events pushed to it
+    fn with_output(stdout: Box<dyn Write + Send>, columns: u16, rows: u16, terminal: bool) -> Self {
         let mut colors = BiMap::new();
         colors.insert(0, Color::new(0, 0, 0));
+        let seed = rand::random();
 
-        Ok(Rael {
-            widht: win.columns,
-            height: win.rows * 2,
+        Rael {
+            widht: columns.min(MAX as u16),
+            height: rows.min(MAX as u16 / 2) * 2,
             pixels: [[0; MAX]; MAX],
             z_buffer: [[0; MAX]; MAX],
             colors,
             stdout,
-            old: Box::new([[1; MAX]; MAX]),
-            inputs: Input::new(reader),
-            chars: [[' '; MAX]; MAX / 2],
+            // nothing is on screen yet, see `repaint`
+            old: Box::new([[u16::MAX; MAX]; MAX]),
+            #[cfg(feature = "input")]
+            inputs: Input::manual(),
+            chars: [[Glyph::BLANK; MAX]; MAX / 2],
             dirty_rows: [0; 2],
-            old_chars: Box::new([[' '; MAX]; MAX / 2]),
-        })
+            old_chars: Box::new([[Glyph::BLANK; MAX]; MAX / 2]),
+            graphemes: BiMap::new(),
+            #[cfg(all(feature = "input", feature = "capture"))]
+            screenshot_key: Some(KeyCode::F(12)),
+            #[cfg(feature = "capture")]
+            screenshot_dir: PathBuf::from("screenshots"),
+            #[cfg(all(feature = "input", feature = "capture"))]
+            last_screenshot: None,
+            cast: None,
+            broadcast: None,
+            #[cfg(feature = "capture")]
+            clip: None,
+            clock: Clock::default(),
+            stats: Stats::default(),
+            camera: Camera::default(),
+            scroll_regions: true,
+            bandwidth: Bandwidth::default(),
+            deferred_rows: [0; 2],
+            next_row: 0,
+            #[cfg(feature = "input")]
+            stats_key: Some(KeyCode::F(3)),
+            #[cfg(feature = "input")]
+            suspend_key: Some((KeyCode::Char('z'), KeyModifiers::CONTROL)),
+            #[cfg(feature = "input")]
+            focus: FocusPolicy::default(),
+            last_render: None,
+            rng: StdRng::seed_from_u64(seed),
+            seed,
+            frame: Vec::new(),
+            terminal,
+            inline: None,
+            deltarune: 1.0,
+            viewport: None,
+            remote: false,
+            hangup: None,
+        }
     }
 
-    fn get_or_insert_color(&mut self, color: Color) -> u16 {
+    fn try_color(&mut self, color: Color) -> Result<u16, RaelError> {
         //*self.colors.entry(color).or_insert_with(|| {
         //    let new_index = self.colors.len() as u16;
         //    self.colors.push(color);
         //    new_index
         //})
-        if self.colors.contains_right(&color) {
-            *self.colors.get_by_right(&color).unwrap()
+        if let Some(index) = self.colors.get_by_right(&color) {
+            Ok(*index)
         } else {
-            let new_index = self.colors.len() as u16;
+            // u16::MAX is what `repaint` fills `old` with, it must never match a pixel
+            let new_index = u16::try_from(self.colors.len())
+                .ok()
+                .filter(|i| *i < u16::MAX)
+                .ok_or(RaelError::PaletteExhausted)?;
             self.colors.insert(new_index, color);
-            new_index
+            Ok(new_index)
+        }
+    }
+
+    // checked draw calls only accept what's on the canvas, pixel coordinates
+    fn check_bounds(&self, x: usize, y: usize) -> Result<(), RaelError> {
+        let (width, height) = self.canvas_size();
+        if x < width && y < height {
+            Ok(())
+        } else {
+            Err(RaelError::OutOfBounds { x, y })
         }
     }
 
     pub fn set_pixel(&mut self, x: usize, y: usize, z: u8, color: Color) {
-        if x > MAX || y > MAX {
+        if x >= MAX || y >= MAX {
             panic!("y={y} and x={x}, one of them exceeds MAX:{MAX}");
         }
+        if let Err(e) = self.place_pixel(x, y, z, color) {
+            panic!("{e}");
+        }
+    }
+
+    /// Like `set_pixel`, but errors instead of panicking, and outside of the canvas too
+    pub fn try_set_pixel(
+        &mut self,
+        x: usize,
+        y: usize,
+        z: u8,
+        color: Color,
+    ) -> Result<(), RaelError> {
+        self.check_bounds(x, y)?;
+        self.place_pixel(x, y, z, color)
+    }
+
+    pub(crate) fn put_pixel(
+        &mut self,
+        x: usize,
+        y: usize,
+        z: u8,
+        color: Color,
+    ) -> Result<(), RaelError> {
         if self.z_buffer[y][x] <= z {
-            self.pixels[y][x] = self.get_or_insert_color(color);
+            self.pixels[y][x] = self.try_color(color)?;
             self.z_buffer[y][x] = z;
             self.dirty_rows[(y / 2) / 128] |= 1 << ((y / 2) % 128);
         }
+        Ok(())
+    }
+
+    fn get_or_insert_grapheme(&mut self, grapheme: &str) -> Glyph {
+        let mut chars = grapheme.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return Glyph::from_char(c);
+        }
+        if let Some(index) = self.graphemes.get_by_right(grapheme) {
+            Glyph::from_cluster(*index)
+        } else {
+            let new_index = self.graphemes.len() as u32;
+            self.graphemes.insert(new_index, grapheme.to_string());
+            Glyph::from_cluster(new_index)
+        }
+    }
+
+    /// The text a glyph stands for, continuation cells are empty
+    pub fn glyph_str(&self, glyph: Glyph) -> &str {
+        if let Some(index) = glyph.cluster_index() {
+            self.graphemes
+                .get_by_left(&index)
+                .map_or("", |g| g.as_str())
+        } else {
+            ""
+        }
     }
 
     pub fn set_text(&mut self, x: usize, y: usize, z: u8, bg: Color, fg: Color, cchar: char) {
         if x > MAX || y > MAX / 2 {
             panic!("y={y} and x={x}, one of them exceeds MAX:{MAX}");
         }
+        let width = grapheme_width(cchar.encode_utf8(&mut [0; 4]));
+        if let Err(e) = self.place_glyph(x, y, z, bg, fg, Glyph::from_char(cchar), width) {
+            panic!("{e}");
+        }
+    }
+
+    /// Like `set_text`, but errors when the char doesn't fully fit on the screen
+    pub fn try_set_text(
+        &mut self,
+        x: usize,
+        y: usize,
+        z: u8,
+        bg: Color,
+        fg: Color,
+        cchar: char,
+    ) -> Result<(), RaelError> {
+        let width = grapheme_width(cchar.encode_utf8(&mut [0; 4]));
+        self.check_bounds(x + width - 1, y)?;
+        self.place_glyph(x, y, z, bg, fg, Glyph::from_char(cchar), width)
+    }
+
+    /// Writes a whole string starting at `x`, one grapheme cluster per cell (two for wide ones).
+    /// Returns how many cells were used, so you can keep writing right after it.
+    pub fn set_str(
+        &mut self,
+        x: usize,
+        y: usize,
+        z: u8,
+        bg: Color,
+        fg: Color,
+        text: &str,
+    ) -> usize {
+        if x > MAX || y > MAX / 2 {
+            panic!("y={y} and x={x}, one of them exceeds MAX:{MAX}");
+        }
+        let mut cx = x;
+        for (grapheme, width) in graphemes(text) {
+            if cx + width > MAX {
+                break;
+            }
+            let glyph = self.get_or_insert_grapheme(grapheme);
+            if let Err(e) = self.place_glyph(cx, y, z, bg, fg, glyph, width) {
+                panic!("{e}");
+            }
+            cx += width;
+        }
+        cx - x
+    }
+
+    /// Like `set_str`, but errors without drawing anything when the text doesn't fit on
+    /// the screen, instead of cutting it
+    pub fn try_set_str(
+        &mut self,
+        x: usize,
+        y: usize,
+        z: u8,
+        bg: Color,
+        fg: Color,
+        text: &str,
+    ) -> Result<usize, RaelError> {
+        self.check_bounds(x, y)?;
+        let width: usize = graphemes(text).map(|(_, width)| width).sum();
+        if width > 0 {
+            self.check_bounds(x + width - 1, y)?;
+        }
+        let mut cx = x;
+        for (grapheme, width) in graphemes(text) {
+            let glyph = self.get_or_insert_grapheme(grapheme);
+            self.place_glyph(cx, y, z, bg, fg, glyph, width)?;
+            cx += width;
+        }
+        Ok(cx - x)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn put_glyph(
+        &mut self,
+        x: usize,
+        y: usize,
+        z: u8,
+        bg: Color,
+        fg: Color,
+        glyph: Glyph,
+        width: usize,
+    ) -> Result<(), RaelError> {
         let y = if y.is_multiple_of(2) { y } else { y - 1 };
-        if self.z_buffer[y][x] <= z {
-            self.pixels[y][x] = self.get_or_insert_color(bg);
-            self.z_buffer[y][x] = z;
-            self.chars[y / 2][x] = cchar;
-            self.pixels[y + 1][x] = self.get_or_insert_color(fg);
-            self.dirty_rows[(y / 2) / 128] |= 1 << ((y / 2) % 128);
+        // a wide glyph sticking out of the grid becomes a blank, terminals would wrap it
+        let (glyph, width) = if x + width > self.widht as usize {
+            (Glyph::BLANK, 1)
+        } else {
+            (glyph, width)
+        };
+        if (x..x + width).any(|cx| self.z_buffer[y][cx] > z) {
+            return Ok(());
+        }
+        let row = y / 2;
+        // we might be cutting a wide glyph in half, the other half becomes a blank
+        if self.chars[row][x].is_continuation() && x > 0 {
+            self.chars[row][x - 1] = Glyph::BLANK;
+        }
+        if x + width < MAX && self.chars[row][x + width].is_continuation() {
+            self.chars[row][x + width] = Glyph::BLANK;
         }
+        let bg = self.try_color(bg)?;
+        let fg = self.try_color(fg)?;
+        for cx in x..x + width {
+            self.pixels[y][cx] = bg;
+            self.pixels[y + 1][cx] = fg;
+            self.z_buffer[y][cx] = z;
+            self.chars[row][cx] = if cx == x { glyph } else { Glyph::CONTINUATION };
+        }
+        self.dirty_rows[row / 128] |= 1 << (row % 128);
+        Ok(())
     }
 
     pub fn set_image<const W: usize, const H: usize>(
@@ -174,16 +497,35 @@ impl Rael {
         }
     }
 
+    /// Like `set_image`, but errors without drawing anything when the image doesn't fully
+    /// fit on the screen
+    pub fn try_set_image<const W: usize, const H: usize>(
+        &mut self,
+        image: ImageAsset<W, H>,
+        pos: (usize, usize, u8),
+    ) -> Result<(), RaelError> {
+        let (ox, oy, oz) = pos;
+        self.check_bounds(ox, oy)?;
+        self.check_bounds(ox + W.max(1) - 1, oy + H.max(1) - 1)?;
+        for y in 0..H {
+            for x in 0..W {
+                let color_index = image.pixels[y][x] as usize;
+                if let Some(color) = image.colors.get(color_index) {
+                    self.put_pixel(ox + x, oy + y, oz, *color)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
     pub fn clear(&mut self) {
-        *self.old = self.pixels;
-        *self.old_chars = self.chars;
         self.dirty_rows = [0; 2];
         for y in 0..self.height as usize {
             let pixel_y1 = y * 2;
             let pixel_y2 = y * 2 + 1;
 
             for x in 0..self.widht as usize {
-                let char_dirty = self.chars[y][x] != ' ';
+                let char_dirty = self.chars[y][x] != Glyph::BLANK;
                 let pixel_dirty = self.pixels[pixel_y1][x] != 0
                     || (pixel_y2 < MAX && self.pixels[pixel_y2][x] != 0);
 
@@ -195,7 +537,7 @@ impl Rael {
         }
         self.z_buffer = [[0; MAX]; MAX];
         self.pixels = [[0; MAX]; MAX];
-        self.chars = [[' '; MAX]; MAX / 2];
+        self.chars = [[Glyph::BLANK; MAX]; MAX / 2];
     }
 
     pub fn clear_colors(&mut self) {
@@ -206,141 +548,296 @@ impl Rael {
 
     pub fn force_clear(&mut self) {
         // all those clears was not enough, so i made this >:3
-        *self.old = [[255; MAX]; MAX];
+        *self.old = [[u16::MAX; MAX]; MAX];
         self.pixels = [[0; MAX]; MAX];
         self.z_buffer = [[0; MAX]; MAX];
-        self.chars = [[' '; MAX]; MAX / 2];
+        self.chars = [[Glyph::BLANK; MAX]; MAX / 2];
         self.dirty_rows = [u128::MAX; 2];
         self.clear_colors();
+        self.graphemes = BiMap::new();
     }
 
-    pub async fn render(&mut self, deltarune: Option<f32>) -> io::Result<()> {
-        let deltarune = deltarune.unwrap_or(1.0);
-        queue!(self.stdout, BeginSynchronizedUpdate)?;
+    /// How the cell at `(x, row)` gets drawn, `render` and the exporters both go through this
+    pub(crate) fn cell_paint(&self, x: usize, row: usize, deltarune: f32) -> CellPaint {
+        self.paint(
+            self.pixels[row * 2][x],
+            self.pixels[row * 2 + 1][x],
+            self.chars[row][x],
+            deltarune,
+        )
+    }
 
-        for bucket in 0..2 {
-            let mut yummy_bits = self.dirty_rows[bucket];
+    // a cell from its two pixels and glyph
+    pub(crate) fn paint(&self, top: u16, bottom: u16, glyph: Glyph, deltarune: f32) -> CellPaint {
+        let color = |index: u16| {
+            self.colors
+                .get_by_left(&index)
+                .unwrap()
+                .make_it_more_deltarune(deltarune)
+        };
+        let (color_top, color_bottom) = (color(top), color(bottom));
+        if glyph != Glyph::BLANK {
+            CellPaint {
+                fg: Some(color_bottom),
+                bg: color_top,
+                glyph,
+            }
+        } else if color_top == color_bottom {
+            CellPaint {
+                fg: None,
+                bg: color_top,
+                glyph: Glyph::BLANK,
+            }
+        } else {
+            CellPaint {
+                fg: Some(color_bottom),
+                bg: color_top,
+                glyph: Glyph::from_char('▄'),
+            }
+        }
+    }
 
-            if yummy_bits == 0 {
+    /// Prints what changed since the last frame, `deltarune` dims the whole frame
+    /// (1.0 is full brightness). Doesn't need any runtime, see [`Rael::render_blocking`].
+    #[cfg(feature = "async")]
+    pub async fn render(&mut self, deltarune: Option<f32>) -> Result<(), RaelError> {
+        self.render_blocking(deltarune)
+    }
+
+    /// `render` for code that isn't async
+    pub fn render_blocking(&mut self, deltarune: Option<f32>) -> Result<(), RaelError> {
+        // throttled while unfocused, the skipped frame still counts for the input. The next
+        // `clear` forgets its dirty rows, so the frame that does print takes them over
+        #[cfg(feature = "input")]
+        if !self.update_focus() {
+            for (deferred, dirty) in self.deferred_rows.iter_mut().zip(self.dirty_rows) {
+                *deferred |= dirty;
+            }
+            return self.inputs.next_frame();
+        }
+        let deltarune = deltarune.unwrap_or(1.0);
+        self.deltarune = deltarune;
+        let started = Instant::now();
+        let mut frame_stats = FrameStats {
+            frame_time: self.last_render.map_or(Duration::ZERO, |t| started - t),
+            ..FrameStats::default()
+        };
+        self.last_render = Some(started);
+
+        if self.terminal && signals::take_resumed() {
+            self.repaint();
+        }
+        #[cfg(feature = "input")]
+        if let Some(key) = self.stats_key
+            && self.inputs.take_key_press(key)
+        {
+            self.stats.overlay = !self.stats.overlay;
+        }
+        // overlays are about the terminal, not the canvas, and they only last for this frame
+        let viewport = self.viewport.take();
+        let mut overlays = Vec::new();
+        if self.stats.overlay {
+            overlays.extend(self.draw_stats_overlay());
+        }
+        #[cfg(feature = "input")]
+        overlays.extend(self.draw_focus_overlay());
+        self.viewport = viewport;
+
+        let origin = self.inline.unwrap_or(0);
+        queue!(self.frame, BeginSynchronizedUpdate)?;
+        frame_stats.scrolled_rows = self.scroll_frame(origin)?;
+
+        // rows left over by the previous frames come first
+        let rows = self.height as usize / 2;
+        let budget = self.bandwidth.frame_budget(frame_stats.frame_time);
+        let start = self.next_row % rows.max(1);
+        let mut deferred = [0u128; 2];
+        for i in 0..rows {
+            let y = (start + i) % rows;
+            let (bucket, bit) = (y / 128, 1u128 << (y % 128));
+            if (self.dirty_rows[bucket] | self.deferred_rows[bucket]) & bit == 0 {
+                continue;
+            }
+            if budget.is_some_and(|budget| self.frame.len() >= budget) {
+                if deferred == [0; 2] {
+                    self.next_row = y;
+                }
+                deferred[bucket] |= bit;
                 continue;
             }
+            let render_y = y * 2;
+            frame_stats.dirty_rows += 1;
+            queue!(self.frame, cursor::MoveTo(0, origin + y as u16))?;
+            let row_top = self.pixels[render_y];
+            let row_bottom = self.pixels[render_y + 1];
+            let old_row_top = self.old[render_y];
+            let old_row_bottom = self.old[render_y + 1];
+            let chars = self.chars[y];
+            let old_chars = self.old_chars[y];
+
+            //let mut skip_count = 0;
+            let mut printed_wide = false;
 
-            while yummy_bits != 0 {
-                let mini_bit = yummy_bits.trailing_zeros() as usize;
-                let y = (bucket * 128) + mini_bit;
-
-                let render_y = y * 2;
-
-                if render_y < self.height.into() {
-                    queue!(self.stdout, cursor::MoveTo(0, y as u16))?;
-                    let row_top = self.pixels[render_y];
-                    let row_bottom = self.pixels[render_y + 1];
-                    let old_row_top = self.old[render_y];
-                    let old_row_bottom = self.old[render_y + 1];
-                    let chars = self.chars[y];
-                    let old_chars = self.old_chars[y];
-
-                    //let mut skip_count = 0;
-
-                    for x in 0..self.widht as usize {
-                        let top = row_top[x];
-                        let bottom = row_bottom[x];
-
-                        let old_top = old_row_top[x];
-                        let old_bottom = old_row_bottom[x];
-
-                        let (new_char, old_char) = (chars[x], old_chars[x]);
-                        if top == old_top && bottom == old_bottom && new_char == old_char {
-                            queue!(self.stdout, cursor::MoveRight(1))?;
-                            continue;
-                        }
-
-                        //if skip_count > 0 {
-                        //    queue!(self.stdout, cursor::MoveTo(0, skip_count))?;
-                        //}
-
-                        let color_top = self
-                            .colors
-                            .get_by_left(&top)
-                            .unwrap()
-                            .make_it_more_deltarune(deltarune);
-                        let color_bottom = self
-                            .colors
-                            .get_by_left(&bottom)
-                            .unwrap()
-                            .make_it_more_deltarune(deltarune);
-                        if new_char != ' ' {
-                            queue!(
-                                self.stdout,
-                                SetForegroundColor(CrosstermColor::Rgb {
-                                    r: color_bottom.r,
-                                    g: color_bottom.g,
-                                    b: color_bottom.b
-                                }),
-                                SetBackgroundColor(CrosstermColor::Rgb {
-                                    r: color_top.r,
-                                    g: color_top.g,
-                                    b: color_top.b
-                                }),
-                                Print(new_char)
-                            )?;
-                        } else if color_top == color_bottom {
-                            queue!(
-                                self.stdout,
-                                SetBackgroundColor(CrosstermColor::Rgb {
-                                    r: color_top.r,
-                                    g: color_top.g,
-                                    b: color_top.b
-                                }),
-                                Print(" ")
-                            )?;
-                        } else {
-                            queue!(
-                                self.stdout,
-                                SetForegroundColor(CrosstermColor::Rgb {
-                                    r: color_bottom.r,
-                                    g: color_bottom.g,
-                                    b: color_bottom.b
-                                }),
-                                SetBackgroundColor(CrosstermColor::Rgb {
-                                    r: color_top.r,
-                                    g: color_top.g,
-                                    b: color_top.b
-                                }),
-                                Print("▄")
-                            )?;
-                        }
-                    }
+            for x in 0..self.widht as usize {
+                let new_char = chars[x];
+                // the right half of a wide glyph got printed along with its left half
+                if new_char.is_continuation() && std::mem::take(&mut printed_wide) {
+                    continue;
+                }
+                let is_wide = x + 1 < MAX && chars[x + 1].is_continuation();
+                let cell_changed = |x: usize| {
+                    row_top[x] != old_row_top[x]
+                        || row_bottom[x] != old_row_bottom[x]
+                        || chars[x] != old_chars[x]
+                };
+                if !(cell_changed(x) || is_wide && cell_changed(x + 1)) {
+                    queue!(self.frame, cursor::MoveRight(1))?;
+                    continue;
                 }
+                printed_wide = is_wide;
 
-                yummy_bits &= !(1 << mini_bit);
+                //if skip_count > 0 {
+                //    queue!(self.stdout, cursor::MoveTo(0, skip_count))?;
+                //}
+
+                let paint = self.cell_paint(x, y, deltarune);
+                queue_cell(&mut self.frame, &self.graphemes, &paint)?;
+                frame_stats.cells_changed += 1;
             }
+
+            // `old` is what the terminal shows
+            self.old[render_y] = row_top;
+            self.old[render_y + 1] = row_bottom;
+            self.old_chars[y] = chars;
         }
-        queue!(self.stdout, EndSynchronizedUpdate)?;
-        self.stdout.flush()?;
+        frame_stats.deferred_rows = (deferred[0].count_ones() + deferred[1].count_ones()) as usize;
+        self.deferred_rows = deferred;
+        if deferred == [0; 2] {
+            self.next_row = 0;
+        }
+        queue!(self.frame, EndSynchronizedUpdate)?;
+
+        #[cfg(feature = "input")]
+        // the game may have taken the resize out of the snapshot already, the recorder
+        // knows the size it wrote last
+        if let Some(cast) = &mut self.cast
+            && let Some(size) = self.inputs.size()
+        {
+            cast.resize(size)?;
+        }
+        frame_stats.bytes_written = self.frame.len();
+        self.present()?;
+        self.sync_spectators(deltarune)?;
         self.dirty_rows = [0; 2];
+        // the latest overlay first, the first one saved what the game drew
+        for under in overlays.into_iter().rev() {
+            self.restore_under_overlay(under);
+        }
+        frame_stats.palette_size = self.colors.len();
+        frame_stats.render_time = started.elapsed();
+        self.stats.push(frame_stats);
+        #[cfg(feature = "input")]
+        self.inputs.next_frame()?;
+
+        #[cfg(feature = "capture")]
+        if let Some((x, y, w, h)) = self.clip.as_ref().map(|c| c.region) {
+            let image = self.frame_image(x, y, w, h);
+            if let Some(clip) = &mut self.clip {
+                clip.capture(image);
+            }
+        }
+
+        #[cfg(all(feature = "input", feature = "capture"))]
+        if let Some(key) = self.screenshot_key
+            && self.inputs.take_key_press(key)
+        {
+            // a failed screenshot shouldn't take the game down with it
+            self.last_screenshot = Some(self.screenshot_to_dir());
+        }
+        #[cfg(feature = "input")]
+        if let Some((key, modifiers)) = self.suspend_key
+            && self.terminal
+            && self.inputs.take_key_combo(key, modifiers)
+        {
+            signals::suspend();
+        }
+        Ok(())
+    }
+
+    pub fn render_custom(&mut self, string: String) -> Result<(), RaelError> {
+        self.frame.extend_from_slice(string.as_bytes());
+        self.present()
+    }
+
+    // sends the frame to the terminal and to whoever else is listening
+    fn present(&mut self) -> Result<(), RaelError> {
+        // suspended, the frame would land in the shell, resuming repaints everything anyway
+        if self.terminal && !terminal::is_active() {
+            self.frame.clear();
+            return Ok(());
+        }
+        let started = Instant::now();
+        self.stdout.write_all(&self.frame)?;
+        self.stdout.flush()?;
+        self.bandwidth.measure(self.frame.len(), started.elapsed());
+        if let Some(cast) = &mut self.cast {
+            cast.output(&self.frame)?;
+        }
+        self.broadcast_frame();
+        self.frame.clear();
         Ok(())
     }
 
-    pub fn render_custom(&mut self, string: String) -> io::Result<()> {
-        self.stdout.write_all(string.as_bytes())?;
+    /// Makes the next `render` redraw every cell, without touching the framebuffer
+    pub fn repaint(&mut self) {
+        *self.old = [[u16::MAX; MAX]; MAX];
+        self.dirty_rows = [u128::MAX; 2];
+    }
+
+    /// Starts recording everything Rael prints into an asciicast v2 file.
+    /// The first frame is a full repaint so the recording doesn't start on a blank screen.
+    pub fn record_cast(&mut self, path: impl AsRef<Path>) -> Result<(), RaelError> {
+        let mut cast = CastRecorder::create(path, (self.widht, self.height / 2))?;
+        cast.output(b"\x1b[2J\x1b[?25l")?;
+        self.cast = Some(cast);
+        self.repaint();
         Ok(())
     }
+
+    /// The seed `rng` started from
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Restarts `rng` from a seed
+    pub fn reseed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
+    /// Stops the asciicast recording and flushes the file
+    pub fn stop_cast(&mut self) -> Result<(), RaelError> {
+        match self.cast.take() {
+            Some(mut cast) => cast.flush(),
+            None => Ok(()),
+        }
+    }
 }
 
 impl Drop for Rael {
     fn drop(&mut self) {
-        let _ = execute!(
-            self.stdout,
-            DisableMouseCapture,
-            DisableFocusChange,
-            PopKeyboardEnhancementFlags,
-            EndSynchronizedUpdate,
-            EnableLineWrap,
-            LeaveAlternateScreen,
-            Show
-        );
-        let _ = disable_raw_mode();
+        #[cfg(feature = "capture")]
+        let _ = self.finish_clip();
         let _ = self.stdout.flush();
+        if let Some(origin) = self.inline {
+            let _ = self.leave_inline(origin);
+        }
+        if self.remote {
+            let _ = terminal::leave_remote(&mut self.stdout);
+        }
+        if !self.terminal {
+            return;
+        }
+        let _ = terminal::leave(&mut self.stdout);
     }
 }
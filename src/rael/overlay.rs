@@ -0,0 +1,35 @@
+use crate::rael::{Glyph, Rael, MAX};
+
+/// What an overlay covered, put back once the frame is out so overlays never end up in the
+/// game's framebuffer, its queries or its captures.
+pub(crate) struct UnderOverlay {
+    top: usize,
+    pixels: Vec<[u16; MAX]>,
+    z_buffer: Vec<[u8; MAX]>,
+    chars: Vec<[Glyph; MAX]>,
+}
+
+impl Rael {
+    // saves the cell rows `rows` before an overlay gets drawn over them
+    pub(crate) fn save_under_overlay(&self, rows: std::ops::RangeInclusive<usize>) -> UnderOverlay {
+        let (first, last) = (*rows.start(), *rows.end());
+        UnderOverlay {
+            top: first * 2,
+            pixels: self.pixels[first * 2..(last + 1) * 2].to_vec(),
+            z_buffer: self.z_buffer[first * 2..(last + 1) * 2].to_vec(),
+            chars: self.chars[first..=last].to_vec(),
+        }
+    }
+
+    // puts back what the overlay covered, the next frame prints those rows again
+    pub(crate) fn restore_under_overlay(&mut self, under: UnderOverlay) {
+        let rows = under.pixels.len();
+        self.pixels[under.top..under.top + rows].copy_from_slice(&under.pixels);
+        self.z_buffer[under.top..under.top + rows].copy_from_slice(&under.z_buffer);
+        let first = under.top / 2;
+        self.chars[first..first + under.chars.len()].copy_from_slice(&under.chars);
+        for y in first..first + under.chars.len() {
+            self.dirty_rows[y / 128] |= 1u128 << (y % 128);
+        }
+    }
+}
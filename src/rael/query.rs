@@ -0,0 +1,124 @@
+use crate::rael::{Color, Glyph, Rael};
+
+/// One cell of the text layer as it would be rendered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextCell {
+    pub glyph: Glyph,
+    /// The grapheme in the cell, empty for the right half of a wide glyph
+    pub text: String,
+    pub bg: Color,
+    pub fg: Color,
+}
+
+impl Rael {
+    fn in_bounds(&self, x: usize, y: usize) -> bool {
+        x < self.widht as usize && y < self.height as usize
+    }
+
+    fn color_at(&self, x: usize, y: usize) -> Color {
+        // every index in the framebuffer comes from get_or_insert_color
+        *self
+            .colors
+            .get_by_left(&self.pixels[y][x])
+            .unwrap_or(&Color::new(0, 0, 0))
+    }
+
+    /// Color of the pixel at `(x, y)`, `None` outside of the canvas.
+    ///
+    /// Like the draw calls, queries take canvas coordinates when a logical size is set.
+    pub fn get_pixel(&self, x: usize, y: usize) -> Option<Color> {
+        let (x, y) = self.to_physical(x, y)?;
+        self.pixel_at(x, y)
+    }
+
+    /// The z stored for the pixel at `(x, y)`, `None` outside of the canvas
+    pub fn get_depth(&self, x: usize, y: usize) -> Option<u8> {
+        let (x, y) = self.to_physical(x, y)?;
+        self.in_bounds(x, y).then(|| self.z_buffer[y][x])
+    }
+
+    /// The text cell covering the pixel at `(x, y)`, same coordinates as `set_text`
+    pub fn get_cell(&self, x: usize, y: usize) -> Option<TextCell> {
+        // text lands on the cell under the top of its canvas cell, see place_glyph
+        let (x, y) = self.to_physical(x, y & !1)?;
+        self.cell_at(x, y)
+    }
+
+    /// Colors of a pixel row from left to right, empty outside of the canvas
+    pub fn row(&self, y: usize) -> impl Iterator<Item = Color> + '_ {
+        let (width, height) = self.canvas_size();
+        let width = if y < height { width } else { 0 };
+        (0..width).filter_map(move |x| self.get_pixel(x, y))
+    }
+
+    /// Every pixel of a rectangle as `(x, y, color)`, row by row.
+    /// The rectangle gets clipped to the canvas.
+    pub fn region(
+        &self,
+        x: usize,
+        y: usize,
+        w: usize,
+        h: usize,
+    ) -> impl Iterator<Item = (usize, usize, Color)> + '_ {
+        let (width, height) = self.canvas_size();
+        let right = (x + w).min(width);
+        let bottom = (y + h).min(height);
+        (y..bottom).flat_map(move |py| {
+            (x..right).filter_map(move |px| Some((px, py, self.get_pixel(px, py)?)))
+        })
+    }
+
+    // the queries below work on the terminal pixels, whatever the logical size
+
+    pub(crate) fn pixel_at(&self, x: usize, y: usize) -> Option<Color> {
+        self.in_bounds(x, y).then(|| self.color_at(x, y))
+    }
+
+    pub(crate) fn cell_at(&self, x: usize, y: usize) -> Option<TextCell> {
+        if !self.in_bounds(x, y) {
+            return None;
+        }
+        let y = if y.is_multiple_of(2) { y } else { y - 1 };
+        let glyph = self.chars[y / 2][x];
+        let text = match glyph.as_char() {
+            Some(c) => c.to_string(),
+            None => self.glyph_str(glyph).to_string(),
+        };
+        Some(TextCell {
+            glyph,
+            text,
+            bg: self.color_at(x, y),
+            fg: self.color_at(x, y + 1),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rael::headless::with_headless;
+    use crate::rael::{Color, Scaling};
+
+    #[test]
+    fn queries_take_canvas_coordinates() {
+        let (red, blue) = (Color::new(255, 0, 0), Color::new(0, 0, 255));
+        let (pixel, depth, cell, region, outside) = with_headless(40, 20, move |rael, _| {
+            // 40x40 pixels, scaled 4 times
+            rael.set_logical_size(10, 10, Scaling::Integer);
+            rael.set_pixel(3, 5, 7, red);
+            rael.set_text(2, 4, 0, blue, red, 'x');
+            let region: Vec<_> = rael.region(3, 5, 1, 1).collect();
+            (
+                rael.get_pixel(3, 5),
+                rael.get_depth(3, 5),
+                rael.get_cell(2, 5).map(|cell| cell.text),
+                region,
+                rael.get_pixel(10, 0),
+            )
+        });
+        assert_eq!(pixel, Some(red));
+        assert_eq!(depth, Some(7));
+        assert_eq!(cell.as_deref(), Some("x"));
+        assert_eq!(region, vec![(3, 5, red)]);
+        assert_eq!(outside, None);
+    }
+}
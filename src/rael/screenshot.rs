@@ -0,0 +1,165 @@
+use crate::rael::{Color, Glyph, Rael, RaelError};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The composed frame at pixel resolution, ready to be written as an image.
+pub(crate) struct FrameImage {
+    pub width: usize,
+    pub height: usize,
+    /// RGB, row by row
+    pub rgb: Vec<u8>,
+    /// The text layer, one string per cell row, empty when there is no text at all
+    pub text: Vec<String>,
+}
+
+impl Rael {
+    /// Grabs the part of the framebuffer inside the rectangle, clipped to the screen.
+    ///
+    /// Text cells can't be drawn at pixel resolution, so both of their pixels get the cell
+    /// background and the chars go into `text` instead. Colors are dimmed like the last
+    /// frame printed to the terminal.
+    pub(crate) fn frame_image(&self, x: usize, y: usize, w: usize, h: usize) -> FrameImage {
+        let right = (x + w).min(self.widht as usize);
+        let bottom = (y + h).min(self.height as usize);
+        let (width, height) = (right.saturating_sub(x), bottom.saturating_sub(y));
+
+        let mut rgb = Vec::with_capacity(width * height * 3);
+        for py in y..bottom {
+            let cell_y = py - py % 2;
+            for px in x..right {
+                let row = if self.chars[cell_y / 2][px] != Glyph::BLANK {
+                    cell_y
+                } else {
+                    py
+                };
+                let color = self
+                    .pixel_at(px, row)
+                    .unwrap_or(Color::new(0, 0, 0))
+                    .make_it_more_deltarune(self.deltarune);
+                rgb.extend_from_slice(&[color.r, color.g, color.b]);
+            }
+        }
+
+        let mut text = Vec::new();
+        let mut has_text = false;
+        for cell_y in (y - y % 2..bottom).step_by(2) {
+            let mut line = String::new();
+            for px in x..right {
+                match self.cell_at(px, cell_y) {
+                    Some(cell) if cell.glyph.is_continuation() => {}
+                    Some(cell) if cell.glyph != Glyph::BLANK => {
+                        has_text = true;
+                        line.push_str(&cell.text);
+                    }
+                    _ => line.push(' '),
+                }
+            }
+            text.push(line.trim_end().to_string());
+        }
+        if !has_text {
+            text.clear();
+        }
+
+        FrameImage {
+            width,
+            height,
+            rgb,
+            text,
+        }
+    }
+
+    /// Saves the current frame as a `.png` or `.ppm` (picked from the extension),
+    /// one image pixel per Rael pixel.
+    ///
+    /// The text layer is stored next to the picture: as a `Comment` iTXt chunk (UTF-8) in PNGs
+    /// and as `#` comment lines in the PPM header.
+    pub fn screenshot(&self, path: impl AsRef<Path>) -> Result<(), RaelError> {
+        let path = path.as_ref();
+        let image = self.frame_image(0, 0, self.widht as usize, self.height as usize);
+        let mut out = BufWriter::new(File::create(path)?);
+
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("png") => write_png(&mut out, &image)?,
+            Some("ppm") => write_ppm(&mut out, &image)?,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "screenshots can only be saved as .png or .ppm",
+                )
+                .into());
+            }
+        }
+        Ok(out.flush()?)
+    }
+
+    /// Takes a screenshot into `screenshot_dir`, named after the current time.
+    /// This is what the screenshot hotkey does.
+    pub fn screenshot_to_dir(&self) -> Result<PathBuf, RaelError> {
+        fs::create_dir_all(&self.screenshot_dir)?;
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis());
+        let path = self.screenshot_dir.join(format!("rael-{millis}.png"));
+        self.screenshot(&path)?;
+        Ok(path)
+    }
+}
+
+fn write_png(out: &mut impl Write, image: &FrameImage) -> io::Result<()> {
+    let mut encoder = png::Encoder::new(out, image.width as u32, image.height as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    if !image.text.is_empty() {
+        // tEXt chunks are latin-1 only, the text layer can be anything
+        encoder.add_itxt_chunk("Comment".to_string(), image.text.join("\n"))?;
+    }
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&image.rgb)?;
+    writer.finish()?;
+    Ok(())
+}
+
+fn write_ppm(out: &mut impl Write, image: &FrameImage) -> io::Result<()> {
+    writeln!(out, "P6")?;
+    for line in &image.text {
+        writeln!(out, "# {line}")?;
+    }
+    writeln!(out, "{} {}", image.width, image.height)?;
+    writeln!(out, "255")?;
+    out.write_all(&image.rgb)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rael::headless::with_headless;
+
+    #[test]
+    fn frame_image_is_dimmed_like_the_terminal() {
+        let rgb = with_headless(4, 2, |rael, _| {
+            rael.set_pixel(0, 0, 0, Color::new(200, 100, 50));
+            rael.render_blocking(Some(0.5)).unwrap();
+            rael.frame_image(0, 0, 1, 1).rgb
+        });
+        assert_eq!(rgb, [100, 50, 25]);
+    }
+
+    #[test]
+    fn png_keeps_text_outside_latin1() {
+        let image = FrameImage {
+            width: 2,
+            height: 2,
+            rgb: vec![0; 12],
+            text: vec!["日本語 🐱".to_string()],
+        };
+        let mut out = Vec::new();
+        write_png(&mut out, &image).unwrap();
+
+        let reader = png::Decoder::new(io::Cursor::new(out)).read_info().unwrap();
+        let chunk = &reader.info().utf8_text[0];
+        assert_eq!(chunk.keyword, "Comment");
+        assert_eq!(chunk.get_text().unwrap(), "日本語 🐱");
+    }
+}
@@ -0,0 +1,138 @@
+use crate::rael::{Glyph, Rael, MAX};
+use crossterm::queue;
+use crossterm::style::Print;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io;
+
+// below this many changed rows, redrawing them is as cheap as scrolling
+const MIN_GAIN: usize = 3;
+
+fn row_hash(
+    pixels: &[[u16; MAX]; MAX],
+    chars: &[[Glyph; MAX]; MAX / 2],
+    row: usize,
+    width: usize,
+) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    pixels[row * 2][..width].hash(&mut hasher);
+    pixels[row * 2 + 1][..width].hash(&mut hasher);
+    chars[row][..width].hash(&mut hasher);
+    hasher.finish()
+}
+
+impl Rael {
+    /// When the frame is the previous one moved up or down by whole cells, scrolls the
+    /// terminal instead of printing every row again: `old` gets shifted the same way, so
+    /// only the rows that scrolled in are left to draw. Returns the shift, positive when
+    /// the content moved up.
+    pub(crate) fn scroll_frame(&mut self, origin: u16) -> io::Result<isize> {
+        let rows = self.height as usize / 2;
+        let width = self.widht as usize;
+        let dirty = (self.dirty_rows[0].count_ones() + self.dirty_rows[1].count_ones()) as usize;
+        if !self.scroll_regions || rows < MIN_GAIN * 2 || dirty < rows / 2 {
+            return Ok(0);
+        }
+
+        let new: Vec<u64> = (0..rows)
+            .map(|row| row_hash(&self.pixels, &self.chars, row, width))
+            .collect();
+        let old: Vec<u64> = (0..rows)
+            .map(|row| row_hash(&self.old, &self.old_chars, row, width))
+            .collect();
+        let matches = |shift: isize| {
+            (0..rows)
+                .filter(|&row| {
+                    let from = row as isize + shift;
+                    from >= 0 && (from as usize) < rows && new[row] == old[from as usize]
+                })
+                .count()
+        };
+
+        let unshifted = matches(0);
+        let max_shift = rows as isize / 2;
+        let (shift, best) = (1..=max_shift)
+            .flat_map(|s| [s, -s])
+            .map(|s| (s, matches(s)))
+            .max_by_key(|&(s, m)| (m, -s.abs()))
+            .unwrap_or((0, 0));
+        if best < unshifted + MIN_GAIN {
+            return Ok(0);
+        }
+
+        // DECSTBM on our rows, scroll, then back to the whole screen
+        let (top, bottom) = (origin as usize + 1, origin as usize + rows);
+        let amount = shift.unsigned_abs();
+        let scroll = if shift > 0 { 'S' } else { 'T' };
+        queue!(
+            self.frame,
+            Print(format!("\x1b[{top};{bottom}r\x1b[{amount}{scroll}\x1b[r"))
+        )?;
+
+        // the terminal now shows the old frame moved, and nothing known in the new rows
+        let moved = amount * 2;
+        if shift > 0 {
+            self.old.copy_within(moved..rows * 2, 0);
+            self.old_chars.copy_within(amount..rows, 0);
+            self.old[rows * 2 - moved..rows * 2].fill([u16::MAX; MAX]);
+        } else {
+            self.old.copy_within(0..rows * 2 - moved, moved);
+            self.old_chars.copy_within(0..rows - amount, amount);
+            self.old[..moved].fill([u16::MAX; MAX]);
+        }
+        // rows that didn't change before can differ from the moved ones
+        self.dirty_rows = [u128::MAX; 2];
+        Ok(shift)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rael::headless::{repainted_screen, screen_after, with_headless};
+    use crate::rael::{Color, Rael};
+
+    // every row gets its own color and text, row `first` of the world on top
+    fn draw_world(rael: &mut Rael, first: usize) {
+        let rows = rael.height as usize / 2;
+        for row in 0..rows {
+            let n = (first + row) as u8;
+            let bg = Color::new(n * 12, 200 - n * 5, n * 7);
+            for x in 0..rael.widht as usize {
+                rael.set_pixel(x, row * 2, 0, bg);
+                rael.set_pixel(x, row * 2 + 1, 0, Color::new(0, n * 3, 90));
+            }
+            rael.set_str(
+                1,
+                row * 2,
+                1,
+                bg,
+                Color::new(255, 255, 255),
+                &format!("line {n}"),
+            );
+        }
+    }
+
+    #[test]
+    fn scrolled_frame_shows_the_same_as_a_repaint() {
+        for shift in [2, -3] {
+            let (scrolled, screen, repainted, full) = with_headless(16, 12, move |rael, output| {
+                draw_world(rael, 5);
+                rael.render_blocking(None).unwrap();
+                let mut bytes = output.take();
+
+                rael.clear();
+                draw_world(rael, (5 + shift) as usize);
+                rael.render_blocking(None).unwrap();
+                let frame = output.take();
+                bytes.extend_from_slice(&frame);
+                let scrolled = rael.stats.last().unwrap().scrolled_rows;
+                let screen = screen_after(rael, &bytes);
+                let repainted = repainted_screen(rael, &output);
+                let full = rael.stats.last().unwrap().bytes_written;
+                (scrolled, screen, repainted, (frame.len(), full))
+            });
+            assert_eq!(scrolled, shift);
+            assert_eq!(screen, repainted);
+            assert!(full.0 < full.1 / 2, "{full:?}");
+        }
+    }
+}
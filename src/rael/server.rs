@@ -0,0 +1,123 @@
+use crate::rael::telnet::{TelnetParser, DO, ECHO, IAC, NAWS, SUPPRESS_GO_AHEAD, WILL};
+use crate::rael::{signals, terminal, Input, Rael, RaelError};
+use crossterm::event::Event;
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// how long a client gets to tell its window size, plain sockets never do
+const NAWS_WAIT: Duration = Duration::from_millis(500);
+// how long a lone ESC waits for the rest of an escape sequence
+const KEY_WAIT: Duration = Duration::from_millis(50);
+// Rael is big, and every client gets a thread
+const CLIENT_STACK: usize = 32 * 1024 * 1024;
+
+/// Accepts connections on `addr` forever, every client plays `game` with its own Rael,
+/// on its own thread. Blocks the calling thread.
+///
+/// An async game can start whatever runtime it likes in `game` and block on itself.
+/// `telnet localhost 4000` works, so does `stty raw -echo; nc localhost 4000` (80x24 then).
+pub fn serve<F>(addr: impl ToSocketAddrs, game: F) -> Result<(), RaelError>
+where
+    F: Fn(Rael) + Send + Sync + 'static,
+{
+    let listener = TcpListener::bind(addr)?;
+    let game = Arc::new(game);
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else {
+            continue;
+        };
+        let game = game.clone();
+        thread::Builder::new()
+            .name("rael-client".into())
+            .stack_size(CLIENT_STACK)
+            .spawn(move || {
+                if let Ok(rael) = Rael::from_tcp(stream) {
+                    game(rael);
+                }
+            })?;
+    }
+    Ok(())
+}
+
+impl Rael {
+    /// A Rael playing on the other end of a socket: the client's terminal gets the frames,
+    /// its keys come back as input, and telnet clients report their window size.
+    ///
+    /// Once the client hangs up, `shutdown_requested` turns true.
+    pub fn from_tcp(stream: TcpStream) -> Result<Self, RaelError> {
+        stream.set_nodelay(true)?;
+        let mut writer = stream.try_clone()?;
+        let mut reader = stream;
+        writer.write_all(&[IAC, WILL, ECHO, IAC, WILL, SUPPRESS_GO_AHEAD, IAC, DO, NAWS])?;
+
+        let mut parser = TelnetParser::default();
+        let mut events = Vec::new();
+        let mut buf = [0; 1024];
+        let deadline = Instant::now() + NAWS_WAIT;
+        let mut size = None;
+        while size.is_none() {
+            let left = deadline.saturating_duration_since(Instant::now());
+            if left.is_zero() {
+                break;
+            }
+            reader.set_read_timeout(Some(left))?;
+            match reader.read(&mut buf) {
+                Ok(0) => return Err(io::Error::from(ErrorKind::UnexpectedEof).into()),
+                Ok(n) => parser.feed(&buf[..n], &mut events),
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => break,
+                Err(e) => return Err(e.into()),
+            }
+            size = events.iter().rev().find_map(|e| match e {
+                Event::Resize(columns, rows) => Some((*columns, *rows)),
+                _ => None,
+            });
+        }
+        reader.set_read_timeout(None)?;
+        let (columns, rows) = size.unwrap_or((80, 24));
+
+        let input = Input::manual();
+        for event in events.drain(..) {
+            input.push(event);
+        }
+        let input_bg = input.clone();
+        let hangup = Arc::new(AtomicBool::new(false));
+        let hangup_bg = hangup.clone();
+        thread::Builder::new()
+            .name("rael-client-input".into())
+            .spawn(move || {
+                let mut events = Vec::new();
+                loop {
+                    // half a key stays buffered, unless nothing follows it for a bit
+                    let wait = parser.waiting().then_some(KEY_WAIT);
+                    if reader.set_read_timeout(wait).is_err() {
+                        break;
+                    }
+                    match reader.read(&mut buf) {
+                        Ok(0) => break,
+                        Ok(n) => parser.feed(&buf[..n], &mut events),
+                        Err(e)
+                            if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) =>
+                        {
+                            parser.flush(&mut events)
+                        }
+                        Err(_) => break,
+                    }
+                    for event in events.drain(..) {
+                        input_bg.push(event);
+                    }
+                }
+                hangup_bg.store(true, Ordering::SeqCst);
+                signals::wake_shutdown();
+            })?;
+
+        terminal::enter_remote(&mut writer)?;
+        let mut rael = Self::with_output(Box::new(writer), columns, rows, false).with_input(input);
+        rael.remote = true;
+        rael.hangup = Some(hangup);
+        Ok(rael)
+    }
+}
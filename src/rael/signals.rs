@@ -0,0 +1,116 @@
+#[cfg(unix)]
+use crate::rael::terminal;
+use crate::rael::Rael;
+#[cfg(feature = "async")]
+use std::future::poll_fn;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+#[cfg(unix)]
+use std::sync::Once;
+#[cfg(feature = "async")]
+use std::task::Poll;
+use std::task::Waker;
+
+#[cfg(unix)]
+static SIGNALS: Once = Once::new();
+static SHUTDOWN: AtomicBool = AtomicBool::new(false);
+static RESUMED: AtomicBool = AtomicBool::new(false);
+static SHUTDOWN_WAKERS: Mutex<Vec<Waker>> = Mutex::new(Vec::new());
+
+/// Starts the thread handling the job control and termination signals, once per process.
+///
+/// - SIGTSTP leaves the terminal modes, stops the process, and enters them again once it
+///   gets continued, the next `render` repaints everything
+/// - SIGCONT alone (after a SIGSTOP) just repaints
+/// - SIGTERM and SIGHUP ask the game to shut down, a second one kills the process
+#[cfg(unix)]
+pub(crate) fn install(title: &str) {
+    use signal_hook::consts::{SIGCONT, SIGHUP, SIGTERM, SIGTSTP};
+    use signal_hook::iterator::Signals;
+    use signal_hook::low_level::emulate_default_handler;
+    use std::io;
+
+    let title = title.to_owned();
+    SIGNALS.call_once(move || {
+        let Ok(mut signals) = Signals::new([SIGTSTP, SIGCONT, SIGTERM, SIGHUP]) else {
+            return;
+        };
+        let _ = std::thread::Builder::new()
+            .name("rael-signals".into())
+            .spawn(move || {
+                for signal in signals.forever() {
+                    match signal {
+                        SIGTSTP => {
+                            let was_active = terminal::is_active();
+                            let _ = terminal::leave(&mut io::stdout());
+                            // blocks until SIGCONT
+                            let _ = emulate_default_handler(SIGTSTP);
+                            if was_active {
+                                let _ = terminal::enter(&mut io::stdout(), &title);
+                            }
+                            RESUMED.store(true, Ordering::SeqCst);
+                        }
+                        SIGCONT => RESUMED.store(true, Ordering::SeqCst),
+                        _ if SHUTDOWN.swap(true, Ordering::SeqCst) => {
+                            let _ = terminal::leave(&mut io::stdout());
+                            let _ = emulate_default_handler(signal);
+                        }
+                        _ => wake_shutdown(),
+                    }
+                }
+            });
+    });
+}
+
+#[cfg(not(unix))]
+pub(crate) fn install(_title: &str) {}
+
+/// Wakes everything waiting in `wait_for_shutdown`, they check again
+#[cfg(any(unix, feature = "server"))]
+pub(crate) fn wake_shutdown() {
+    let wakers = std::mem::take(&mut *SHUTDOWN_WAKERS.lock().unwrap_or_else(|e| e.into_inner()));
+    wakers.into_iter().for_each(Waker::wake);
+}
+
+/// Tells if the process got continued since the last call
+pub(crate) fn take_resumed() -> bool {
+    RESUMED.swap(false, Ordering::SeqCst)
+}
+
+/// Suspends the process like a shell would on Ctrl+Z, returns once it's continued.
+/// Does nothing outside of unix.
+#[cfg(feature = "input")]
+pub(crate) fn suspend() {
+    #[cfg(unix)]
+    let _ = signal_hook::low_level::raise(signal_hook::consts::SIGTSTP);
+}
+
+impl Rael {
+    /// Tells if the process got asked to terminate (SIGTERM or SIGHUP), or the client
+    /// hung up for a Rael served over TCP.
+    ///
+    /// The terminal is left alone so the game can save and quit on its own, a second signal
+    /// kills the process for real.
+    pub fn shutdown_requested(&self) -> bool {
+        SHUTDOWN.load(Ordering::SeqCst)
+            || self
+                .hangup
+                .as_ref()
+                .is_some_and(|h| h.load(Ordering::SeqCst))
+    }
+
+    /// Waits until the process gets asked to terminate, handy in a `select!` next to the game.
+    #[cfg(feature = "async")]
+    pub async fn wait_for_shutdown(&self) {
+        poll_fn(|cx| {
+            let mut wakers = SHUTDOWN_WAKERS.lock().unwrap_or_else(|e| e.into_inner());
+            if self.shutdown_requested() {
+                Poll::Ready(())
+            } else {
+                wakers.push(cx.waker().clone());
+                Poll::Pending
+            }
+        })
+        .await
+    }
+}
@@ -0,0 +1,164 @@
+use crate::rael::overlay::UnderOverlay;
+use crate::rael::{Color, Rael};
+use std::collections::VecDeque;
+use std::time::Duration;
+
+const HISTORY: usize = 120;
+const GRAPH_WIDTH: usize = 40;
+const GRAPH_HEIGHT: usize = 8;
+
+/// Numbers about one `render` call.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FrameStats {
+    /// Time since the previous `render` started
+    pub frame_time: Duration,
+    /// Time spent inside `render`, writing to the terminal included
+    pub render_time: Duration,
+    /// Bytes sent to the terminal
+    pub bytes_written: usize,
+    /// Cells that actually got printed
+    pub cells_changed: usize,
+    /// Cell rows that were looked at
+    pub dirty_rows: usize,
+    /// Cell rows the terminal scrolled the previous frame by, positive is up
+    pub scrolled_rows: isize,
+    /// Dirty rows left for the next frames, the byte budget was used up
+    pub deferred_rows: usize,
+    /// Colors in the palette at the end of the frame
+    pub palette_size: usize,
+}
+
+/// Per-frame statistics collected by Rael, for the last couple of seconds.
+#[derive(Debug, Default)]
+pub struct Stats {
+    history: VecDeque<FrameStats>,
+    /// Draws the stats and a frame time graph in the top left corner
+    pub overlay: bool,
+}
+
+impl Stats {
+    pub(crate) fn push(&mut self, frame: FrameStats) {
+        if self.history.len() == HISTORY {
+            self.history.pop_front();
+        }
+        self.history.push_back(frame);
+    }
+
+    /// The most recent frame
+    pub fn last(&self) -> Option<&FrameStats> {
+        self.history.back()
+    }
+
+    /// The last frames, oldest first
+    pub fn history(&self) -> impl DoubleEndedIterator<Item = &FrameStats> {
+        self.history.iter()
+    }
+
+    /// Average frames per second over the history
+    pub fn fps(&self) -> f64 {
+        let total: Duration = self.history.iter().map(|f| f.frame_time).sum();
+        if total.is_zero() {
+            0.0
+        } else {
+            self.history.len() as f64 / total.as_secs_f64()
+        }
+    }
+
+    /// Average bytes sent per frame over the history
+    pub fn bytes_per_frame(&self) -> f64 {
+        if self.history.is_empty() {
+            return 0.0;
+        }
+        let total: usize = self.history.iter().map(|f| f.bytes_written).sum();
+        total as f64 / self.history.len() as f64
+    }
+}
+
+impl Rael {
+    /// Draws the stats overlay on top of the frame, at z 255 like any other draw call
+    pub(crate) fn draw_stats_overlay(&mut self) -> Option<UnderOverlay> {
+        let rows = self.height as usize / 2;
+        if rows == 0 {
+            return None;
+        }
+        let under = self.save_under_overlay(0..=((2 + GRAPH_HEIGHT - 1) / 2).min(rows - 1));
+        let (bg, fg) = (Color::new(20, 20, 30), Color::new(255, 255, 255));
+        let last = self.stats.last().copied().unwrap_or_default();
+        let line = format!(
+            " {:.1} fps | {:.2} ms | {} B | {} cells | {} rows | {} colors ",
+            self.stats.fps(),
+            last.render_time.as_secs_f64() * 1000.0,
+            last.bytes_written,
+            last.cells_changed,
+            last.dirty_rows,
+            last.palette_size,
+        );
+        self.set_str(0, 0, 255, bg, fg, &line);
+
+        // one column per frame, the bar is full at 33ms (30 fps)
+        let frames: Vec<Duration> = self
+            .stats
+            .history()
+            .rev()
+            .take(GRAPH_WIDTH)
+            .map(|f| f.frame_time)
+            .collect();
+        for x in 0..GRAPH_WIDTH {
+            let frame_time = frames.get(GRAPH_WIDTH - 1 - x).copied().unwrap_or_default();
+            let height = ((frame_time.as_secs_f64() / 0.033) * GRAPH_HEIGHT as f64).ceil() as usize;
+            let bar = if frame_time.as_secs_f64() > 0.033 {
+                Color::new(255, 80, 80)
+            } else {
+                Color::new(80, 255, 120)
+            };
+            for dy in 0..GRAPH_HEIGHT {
+                let color = if GRAPH_HEIGHT - dy <= height { bar } else { bg };
+                self.set_pixel(x, 2 + dy, 255, color);
+            }
+        }
+        Some(under)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rael::headless::with_headless;
+    use crate::rael::{Color, Glyph};
+
+    // what got printed, without the escape sequences
+    fn printed(bytes: &[u8]) -> String {
+        let mut text = String::new();
+        let mut chars = std::str::from_utf8(bytes).unwrap().chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                chars.find(|c| c.is_ascii_alphabetic());
+            } else {
+                text.push(c);
+            }
+        }
+        text
+    }
+
+    #[test]
+    fn overlay_stays_out_of_the_framebuffer() {
+        let red = Color::new(255, 0, 0);
+        let (during, after, pixel, glyph) = with_headless(60, 10, move |rael, output| {
+            rael.set_pixel(0, 0, 0, red);
+            rael.stats.overlay = true;
+            rael.render_blocking(None).unwrap();
+            let during = output.take();
+            let (pixel, glyph) = (rael.get_pixel(0, 0), rael.chars[0][1]);
+
+            rael.stats.overlay = false;
+            rael.render_blocking(None).unwrap();
+            let after = output.take();
+            (during, after, pixel, glyph)
+        });
+        assert!(printed(&during).contains("fps"));
+        assert_eq!(pixel, Some(red));
+        assert_eq!(glyph, Glyph::BLANK);
+        // turning it off paints the game back over it
+        assert!(!printed(&after).contains("fps"));
+        assert!(String::from_utf8_lossy(&after).contains("255;0;0"));
+    }
+}
@@ -0,0 +1,364 @@
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+pub(crate) const IAC: u8 = 255;
+pub(crate) const WILL: u8 = 251;
+const WONT: u8 = 252;
+pub(crate) const DO: u8 = 253;
+const DONT: u8 = 254;
+const SB: u8 = 250;
+const SE: u8 = 240;
+pub(crate) const ECHO: u8 = 1;
+pub(crate) const SUPPRESS_GO_AHEAD: u8 = 3;
+pub(crate) const NAWS: u8 = 31;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum State {
+    #[default]
+    Data,
+    Iac,
+    Negotiation,
+    Sub,
+    SubIac,
+}
+
+/// Turns what a telnet client (or a raw socket) sends into crossterm events: telnet
+/// commands are stripped, window size reports become `Event::Resize`, and the bytes left
+/// are decoded like a legacy terminal would send keys.
+#[derive(Debug, Default)]
+pub(crate) struct TelnetParser {
+    state: State,
+    sub: Vec<u8>,
+    data: Vec<u8>,
+}
+
+impl TelnetParser {
+    pub(crate) fn feed(&mut self, bytes: &[u8], events: &mut Vec<Event>) {
+        for &byte in bytes {
+            self.state = match (self.state, byte) {
+                (State::Data, IAC) => State::Iac,
+                (State::Data, _) => {
+                    self.data.push(byte);
+                    State::Data
+                }
+                (State::Iac, IAC) => {
+                    self.data.push(IAC);
+                    State::Data
+                }
+                (State::Iac, WILL | WONT | DO | DONT) => State::Negotiation,
+                (State::Iac, SB) => {
+                    self.sub.clear();
+                    State::Sub
+                }
+                (State::Iac, _) | (State::Negotiation, _) => State::Data,
+                (State::Sub, IAC) => State::SubIac,
+                (State::Sub, _) => {
+                    self.sub.push(byte);
+                    State::Sub
+                }
+                (State::SubIac, SE) => {
+                    if let [NAWS, w1, w0, h1, h0] = self.sub[..] {
+                        let columns = u16::from_be_bytes([w1, w0]);
+                        let rows = u16::from_be_bytes([h1, h0]);
+                        events.push(Event::Resize(columns, rows));
+                    }
+                    State::Data
+                }
+                (State::SubIac, _) => {
+                    self.sub.push(byte);
+                    State::Sub
+                }
+            };
+        }
+        self.parse_keys(events, false);
+    }
+
+    /// Whether the end of the last read is held back, waiting for the rest of a key
+    pub(crate) fn waiting(&self) -> bool {
+        !self.data.is_empty()
+    }
+
+    /// Gives up on the rest of a key coming: a lone `ESC` is the Esc key after all, and a
+    /// lone `CR` is enter
+    pub(crate) fn flush(&mut self, events: &mut Vec<Event>) {
+        self.parse_keys(events, true);
+    }
+
+    fn parse_keys(&mut self, events: &mut Vec<Event>, flush: bool) {
+        let data = &self.data;
+        let mut i = 0;
+        while i < data.len() {
+            let rest = &data[i..];
+            let Some((key, len)) = parse_key(rest).or_else(|| flush.then(|| cut_short(rest)))
+            else {
+                // the rest of the key comes with the next read
+                break;
+            };
+            if let Some((code, modifiers)) = key {
+                events.push(Event::Key(KeyEvent::new(code, modifiers)));
+            }
+            i += len;
+        }
+        self.data.drain(..i);
+    }
+}
+
+type Key = Option<(KeyCode, KeyModifiers)>;
+
+// the key at the start of `bytes` and how many bytes it took, None when it's incomplete
+fn parse_key(bytes: &[u8]) -> Option<(Key, usize)> {
+    let none = KeyModifiers::NONE;
+    let key = |code| Some((code, none));
+    Some(match bytes[0] {
+        // telnet clients send enter as CR NUL or CR LF
+        b'\r' => {
+            let len = match bytes.get(1)? {
+                b'\0' | b'\n' => 2,
+                _ => 1,
+            };
+            (key(KeyCode::Enter), len)
+        }
+        b'\n' => (key(KeyCode::Enter), 1),
+        b'\t' => (key(KeyCode::Tab), 1),
+        0x7f | 0x08 => (key(KeyCode::Backspace), 1),
+        0x1b => match bytes.get(1)? {
+            b'[' => match parse_csi(&bytes[2..]) {
+                Some((k, l)) => (k, l + 2),
+                // no final byte yet
+                None if bytes[2..].iter().all(|b| (0x20..=0x3f).contains(b)) => return None,
+                None => (key(KeyCode::Esc), 1),
+            },
+            b'O' => (ss3(*bytes.get(2)?), 3),
+            &c if c.is_ascii_graphic() => (Some((KeyCode::Char(c as char), KeyModifiers::ALT)), 2),
+            _ => (key(KeyCode::Esc), 1),
+        },
+        c @ 0x01..=0x1a => (
+            Some((KeyCode::Char((c - 1 + b'a') as char), KeyModifiers::CONTROL)),
+            1,
+        ),
+        c if c < 0x20 => (None, 1),
+        c => {
+            let len = match c {
+                0xc0..=0xdf => 2,
+                0xe0..=0xef => 3,
+                0xf0..=0xf7 => 4,
+                _ => 1,
+            };
+            if bytes.len() < len {
+                return None;
+            }
+            match std::str::from_utf8(&bytes[..len])
+                .ok()
+                .and_then(|s| s.chars().next())
+            {
+                Some(c) if c.is_ascii_uppercase() => {
+                    (Some((KeyCode::Char(c), KeyModifiers::SHIFT)), len)
+                }
+                Some(c) => (key(KeyCode::Char(c)), len),
+                None => (None, len),
+            }
+        }
+    })
+}
+
+// what's left of a key that never got finished
+fn cut_short(bytes: &[u8]) -> (Key, usize) {
+    match bytes[0] {
+        0x1b => (Some((KeyCode::Esc, KeyModifiers::NONE)), 1),
+        b'\r' => (Some((KeyCode::Enter, KeyModifiers::NONE)), 1),
+        // half a utf-8 char
+        _ => (None, bytes.len()),
+    }
+}
+
+// `ESC [` sequences: params, then a final byte
+fn parse_csi(bytes: &[u8]) -> Option<(Key, usize)> {
+    let end = bytes.iter().position(|b| (0x40..=0x7e).contains(b))?;
+    let params: Vec<u16> = std::str::from_utf8(&bytes[..end])
+        .ok()?
+        .split(';')
+        .map(|p| p.parse().unwrap_or(0))
+        .collect();
+    let modifiers = params.get(1).map_or(KeyModifiers::NONE, |&m| {
+        let bits = m.saturating_sub(1);
+        let mut modifiers = KeyModifiers::NONE;
+        modifiers.set(KeyModifiers::SHIFT, bits & 1 != 0);
+        modifiers.set(KeyModifiers::ALT, bits & 2 != 0);
+        modifiers.set(KeyModifiers::CONTROL, bits & 4 != 0);
+        modifiers
+    });
+    let code = match (bytes[end], params[0]) {
+        (b'A', _) => KeyCode::Up,
+        (b'B', _) => KeyCode::Down,
+        (b'C', _) => KeyCode::Right,
+        (b'D', _) => KeyCode::Left,
+        (b'H', _) | (b'~', 1 | 7) => KeyCode::Home,
+        (b'F', _) | (b'~', 4 | 8) => KeyCode::End,
+        (b'Z', _) => KeyCode::BackTab,
+        (b'~', 2) => KeyCode::Insert,
+        (b'~', 3) => KeyCode::Delete,
+        (b'~', 5) => KeyCode::PageUp,
+        (b'~', 6) => KeyCode::PageDown,
+        (b'~', n @ 11..=15) => KeyCode::F((n - 10) as u8),
+        (b'~', n @ 17..=21) => KeyCode::F((n - 11) as u8),
+        (b'~', n @ 23..=24) => KeyCode::F((n - 12) as u8),
+        _ => return Some((None, end + 1)),
+    };
+    Some((Some((code, modifiers)), end + 1))
+}
+
+// `ESC O` sequences, some terminals send those for F1-F4 and the arrows
+fn ss3(byte: u8) -> Key {
+    let code = match byte {
+        b'P' => KeyCode::F(1),
+        b'Q' => KeyCode::F(2),
+        b'R' => KeyCode::F(3),
+        b'S' => KeyCode::F(4),
+        b'A' => KeyCode::Up,
+        b'B' => KeyCode::Down,
+        b'C' => KeyCode::Right,
+        b'D' => KeyCode::Left,
+        b'H' => KeyCode::Home,
+        b'F' => KeyCode::End,
+        _ => return None,
+    };
+    Some((code, KeyModifiers::NONE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> Event {
+        Event::Key(KeyEvent::new(code, modifiers))
+    }
+
+    fn plain(code: KeyCode) -> Event {
+        key(code, KeyModifiers::NONE)
+    }
+
+    // name, the reads, what comes out
+    type Case = (&'static str, &'static [&'static [u8]], Vec<Event>);
+
+    // every read goes through feed on its own
+    fn parse(reads: &[&[u8]]) -> Vec<Event> {
+        let mut parser = TelnetParser::default();
+        let mut events = Vec::new();
+        for read in reads {
+            parser.feed(read, &mut events);
+        }
+        events
+    }
+
+    #[test]
+    fn parses_reads() {
+        let cases: &[Case] = &[
+            (
+                "negotiation is dropped",
+                &[&[IAC, WILL, NAWS, b'a', IAC, DONT, ECHO, b'b']],
+                vec![plain(KeyCode::Char('a')), plain(KeyCode::Char('b'))],
+            ),
+            (
+                "window size",
+                &[&[IAC, SB, NAWS, 0, 120, 0, 40, IAC, SE]],
+                vec![Event::Resize(120, 40)],
+            ),
+            (
+                "window size of 255 columns",
+                &[&[IAC, SB, NAWS, 0, IAC, IAC, 0, 40, IAC, SE]],
+                vec![Event::Resize(255, 40)],
+            ),
+            (
+                "negotiation split across reads",
+                &[&[IAC], &[WILL], &[NAWS, b'x']],
+                vec![plain(KeyCode::Char('x'))],
+            ),
+            ("IAC IAC is a 255 byte", &[&[IAC, IAC]], vec![]),
+            ("CR NUL", &[b"\r\0"], vec![plain(KeyCode::Enter)]),
+            ("CR LF", &[b"\r\n"], vec![plain(KeyCode::Enter)]),
+            ("CR LF split", &[b"\r", b"\n"], vec![plain(KeyCode::Enter)]),
+            ("CR NUL split", &[b"\r", b"\0"], vec![plain(KeyCode::Enter)]),
+            (
+                "CR then a key",
+                &[b"\r", b"a"],
+                vec![plain(KeyCode::Enter), plain(KeyCode::Char('a'))],
+            ),
+            (
+                "arrows",
+                &[b"\x1b[A\x1b[B\x1b[C\x1b[D"],
+                vec![
+                    plain(KeyCode::Up),
+                    plain(KeyCode::Down),
+                    plain(KeyCode::Right),
+                    plain(KeyCode::Left),
+                ],
+            ),
+            ("ss3 arrow", &[b"\x1bOA"], vec![plain(KeyCode::Up)]),
+            (
+                "modified arrow",
+                &[b"\x1b[1;5C"],
+                vec![key(KeyCode::Right, KeyModifiers::CONTROL)],
+            ),
+            (
+                "split after ESC",
+                &[b"\x1b", b"[A"],
+                vec![plain(KeyCode::Up)],
+            ),
+            (
+                "split CSI",
+                &[b"\x1b[1", b";5", b"D"],
+                vec![key(KeyCode::Left, KeyModifiers::CONTROL)],
+            ),
+            ("split ss3", &[b"\x1bO", b"P"], vec![plain(KeyCode::F(1))]),
+            (
+                "split delete",
+                &[b"\x1b[3", b"~"],
+                vec![plain(KeyCode::Delete)],
+            ),
+            (
+                "split utf-8",
+                &[&[0xc3], &[0xa9]],
+                vec![plain(KeyCode::Char('\u{e9}'))],
+            ),
+            (
+                "alt",
+                &[b"\x1bx"],
+                vec![key(KeyCode::Char('x'), KeyModifiers::ALT)],
+            ),
+            (
+                "ESC ESC",
+                &[b"\x1b\x1b[A"],
+                vec![plain(KeyCode::Esc), plain(KeyCode::Up)],
+            ),
+            (
+                "broken CSI",
+                &[b"\x1b[\x01"],
+                vec![
+                    plain(KeyCode::Esc),
+                    plain(KeyCode::Char('[')),
+                    key(KeyCode::Char('a'), KeyModifiers::CONTROL),
+                ],
+            ),
+        ];
+        for (name, reads, expected) in cases {
+            assert_eq!(&parse(reads), expected, "{name}");
+        }
+    }
+
+    #[test]
+    fn flush_gives_up_on_the_rest() {
+        let mut parser = TelnetParser::default();
+        let mut events = Vec::new();
+        parser.feed(b"a\x1b", &mut events);
+        assert!(parser.waiting());
+        assert_eq!(events, vec![plain(KeyCode::Char('a'))]);
+
+        parser.flush(&mut events);
+        assert!(!parser.waiting());
+        assert_eq!(events[1..], [plain(KeyCode::Esc)]);
+
+        parser.feed(b"\r", &mut events);
+        parser.flush(&mut events);
+        parser.feed(b"\n", &mut events);
+        assert_eq!(events[2..], [plain(KeyCode::Enter), plain(KeyCode::Enter)]);
+    }
+}
@@ -0,0 +1,123 @@
+use crossterm::cursor::{Hide, Show};
+use crossterm::event::{
+    DisableFocusChange, DisableMouseCapture, EnableFocusChange, EnableMouseCapture,
+    KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+};
+use crossterm::execute;
+use crossterm::style::ResetColor;
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, DisableLineWrap, EnableLineWrap, EndSynchronizedUpdate,
+    EnterAlternateScreen, LeaveAlternateScreen, SetTitle,
+};
+use std::backtrace::Backtrace;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::panic::{self, PanicHookInfo};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, Once};
+use std::thread::{self, ThreadId};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// whether the real terminal is currently in Rael's modes
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+static PANIC_HOOK: Once = Once::new();
+static PANIC_LOG: Mutex<Option<PathBuf>> = Mutex::new(None);
+// the thread that made the Rael, only its panics take the terminal down
+static OWNER: Mutex<Option<ThreadId>> = Mutex::new(None);
+
+/// Puts the terminal in raw mode, alternate screen and kitty keyboard mode.
+pub(crate) fn enter(out: &mut impl Write, title: &str) -> io::Result<()> {
+    let _ = enable_raw_mode();
+    execute!(
+        out,
+        EnterAlternateScreen,
+        DisableLineWrap,
+        EnableFocusChange,
+        EnableMouseCapture,
+        SetTitle::<&str>(title),
+        Hide,
+        PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::all())
+    )?;
+    ACTIVE.store(true, Ordering::SeqCst);
+    Ok(())
+}
+
+pub(crate) fn is_active() -> bool {
+    ACTIVE.load(Ordering::SeqCst)
+}
+
+/// Undoes [`enter`], only the first call after it does something.
+pub(crate) fn leave(out: &mut impl Write) -> io::Result<()> {
+    if !ACTIVE.swap(false, Ordering::SeqCst) {
+        return Ok(());
+    }
+    let result = execute!(
+        out,
+        DisableMouseCapture,
+        DisableFocusChange,
+        PopKeyboardEnhancementFlags,
+        EndSynchronizedUpdate,
+        EnableLineWrap,
+        LeaveAlternateScreen,
+        Show
+    );
+    let _ = disable_raw_mode();
+    let _ = out.flush();
+    result
+}
+
+/// What a remote terminal gets, no raw mode or keyboard protocol over there
+#[cfg(feature = "server")]
+pub(crate) fn enter_remote(out: &mut impl Write) -> io::Result<()> {
+    execute!(out, EnterAlternateScreen, DisableLineWrap, Hide)
+}
+
+pub(crate) fn leave_remote(out: &mut impl Write) -> io::Result<()> {
+    execute!(out, ResetColor, EnableLineWrap, LeaveAlternateScreen, Show)
+}
+
+/// Where panics get written, `rael-panic.log` in the working directory by default.
+pub fn set_panic_log(path: impl Into<PathBuf>) {
+    *PANIC_LOG.lock().unwrap_or_else(|e| e.into_inner()) = Some(path.into());
+}
+
+/// Makes panics restore the terminal before the message gets printed, otherwise it ends up
+/// in the alternate screen (gone) and the shell stays in raw mode. Installed once by
+/// `Rael::new`, the panic also gets appended to the panic log.
+///
+/// Only panics on the calling thread restore it: a worker thread going down (or a panic
+/// the game catches somewhere else) leaves the game running and on screen.
+pub(crate) fn install_panic_hook() {
+    *OWNER.lock().unwrap_or_else(|e| e.into_inner()) = Some(thread::current().id());
+    PANIC_HOOK.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let owner = *OWNER.lock().unwrap_or_else(|e| e.into_inner());
+            if owner == Some(thread::current().id()) {
+                let _ = leave(&mut io::stdout());
+            }
+            let _ = log_panic(info);
+            previous(info);
+        }));
+    });
+}
+
+fn log_panic(info: &PanicHookInfo) -> io::Result<()> {
+    let path = PANIC_LOG
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .unwrap_or_else(|| PathBuf::from("rael-panic.log"));
+    let mut log = OpenOptions::new().create(true).append(true).open(path)?;
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let thread = thread::current();
+    writeln!(
+        log,
+        "[{time}] thread '{}' {info}",
+        thread.name().unwrap_or("<unnamed>")
+    )?;
+    writeln!(log, "{}\n", Backtrace::force_capture())
+}
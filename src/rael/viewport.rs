@@ -0,0 +1,162 @@
+use crate::rael::{Color, Glyph, Rael, RaelError};
+
+/// How a logical canvas gets stretched over the terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scaling {
+    /// Whole multiples only, every pixel is the same size. Falls back to `Nearest` when the
+    /// terminal is smaller than the canvas
+    Integer,
+    /// As big as the terminal allows, some pixels end up a bit bigger than others
+    Nearest,
+}
+
+// where the canvas lands on the screen, in physical pixels
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Viewport {
+    width: usize,
+    height: usize,
+    scaled_width: usize,
+    scaled_height: usize,
+    x: usize,
+    y: usize,
+}
+
+impl Viewport {
+    fn new(width: usize, height: usize, scaling: Scaling, screen: (usize, usize)) -> Self {
+        let (sw, sh) = screen;
+        let (scaled_width, scaled_height) =
+            if scaling == Scaling::Integer && sw >= width && sh >= height {
+                let scale = (sw / width).min(sh / height);
+                (width * scale, height * scale)
+            } else if sw * height <= sh * width {
+                (sw, height * sw / width)
+            } else {
+                (width * sh / height, sh)
+            };
+        let (scaled_width, scaled_height) = (scaled_width.max(1), scaled_height.max(1));
+        Self {
+            width,
+            height,
+            scaled_width,
+            scaled_height,
+            x: (sw - scaled_width) / 2,
+            // even, so text rows of the canvas start on a cell
+            y: ((sh - scaled_height) / 2) & !1,
+        }
+    }
+
+    fn map_x(&self, x: usize) -> usize {
+        self.x + x * self.scaled_width / self.width
+    }
+
+    fn map_y(&self, y: usize) -> usize {
+        self.y + y * self.scaled_height / self.height
+    }
+}
+
+impl Rael {
+    /// Draws on a `width` x `height` pixels canvas instead of the terminal, scaled to fit
+    /// it and centered with black bars around. Every draw call then takes canvas
+    /// coordinates, and what's drawn outside of the canvas is dropped.
+    ///
+    /// Text isn't scaled: a char lands on the cell under its canvas position, the rest of
+    /// the area it covers gets its background color.
+    ///
+    /// The screen is force cleared, since everything moves.
+    pub fn set_logical_size(&mut self, width: usize, height: usize, scaling: Scaling) {
+        let screen = (self.widht as usize, self.height as usize);
+        self.viewport = Some(Viewport::new(width.max(1), height.max(1), scaling, screen));
+        self.force_clear();
+    }
+
+    /// Goes back to drawing straight on the terminal
+    pub fn clear_logical_size(&mut self) {
+        if self.viewport.take().is_some() {
+            self.force_clear();
+        }
+    }
+
+    /// The size draw calls work with: the logical canvas, or the terminal in pixels
+    pub fn canvas_size(&self) -> (usize, usize) {
+        match self.viewport {
+            Some(vp) => (vp.width, vp.height),
+            None => (self.widht as usize, self.height as usize),
+        }
+    }
+
+    /// Turns a terminal cell, like the ones in mouse events, into the canvas pixel under
+    /// it (the top half of the cell). `None` on the bars around the canvas.
+    pub fn to_logical(&self, column: u16, row: u16) -> Option<(usize, usize)> {
+        let (x, y) = (column as usize, row as usize * 2);
+        let Some(vp) = self.viewport else {
+            return Some((x, y));
+        };
+        if x < vp.x || y < vp.y || x >= vp.x + vp.scaled_width || y >= vp.y + vp.scaled_height {
+            return None;
+        }
+        Some((
+            (x - vp.x) * vp.width / vp.scaled_width,
+            (y - vp.y) * vp.height / vp.scaled_height,
+        ))
+    }
+
+    // the top left physical pixel of a canvas pixel, `None` off the canvas
+    pub(crate) fn to_physical(&self, x: usize, y: usize) -> Option<(usize, usize)> {
+        let Some(vp) = self.viewport else {
+            return Some((x, y));
+        };
+        (x < vp.width && y < vp.height).then(|| (vp.map_x(x), vp.map_y(y)))
+    }
+
+    pub(crate) fn place_pixel(
+        &mut self,
+        x: usize,
+        y: usize,
+        z: u8,
+        color: Color,
+    ) -> Result<(), RaelError> {
+        let Some(vp) = self.viewport else {
+            return self.put_pixel(x, y, z, color);
+        };
+        if x >= vp.width || y >= vp.height {
+            return Ok(());
+        }
+        for py in vp.map_y(y)..vp.map_y(y + 1) {
+            for px in vp.map_x(x)..vp.map_x(x + 1) {
+                self.put_pixel(px, py, z, color)?;
+            }
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn place_glyph(
+        &mut self,
+        x: usize,
+        y: usize,
+        z: u8,
+        bg: Color,
+        fg: Color,
+        glyph: Glyph,
+        width: usize,
+    ) -> Result<(), RaelError> {
+        let Some(vp) = self.viewport else {
+            return self.put_glyph(x, y, z, bg, fg, glyph, width);
+        };
+        let y = y & !1;
+        if x + width > vp.width || y >= vp.height {
+            return Ok(());
+        }
+        let (left, top) = (vp.map_x(x), vp.map_y(y) & !1);
+        let right = vp.map_x(x + width).max(left + width);
+        let bottom = vp.map_y((y + 2).min(vp.height)).max(top + 2);
+        for cy in (top..bottom).step_by(2) {
+            for cx in left..right {
+                if cy != top || cx >= left + width {
+                    self.put_glyph(cx, cy, z, bg, bg, Glyph::BLANK, 1)?;
+                }
+            }
+        }
+        self.put_glyph(left, top, z, bg, fg, glyph, width)
+    }
+}
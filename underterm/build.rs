@@ -1,5 +1,5 @@
 use image::imageops::{resize, FilterType};
-use image::{GenericImageView, Pixel};
+use image::Pixel;
 use std::collections::HashMap;
 use std::fs::{self, File};
 use std::io::{BufWriter, Write};
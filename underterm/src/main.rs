@@ -0,0 +1,56 @@
+mod assets;
+mod underterm;
+
+use crate::underterm::Map;
+use rael::*;
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<(), RaelError> {
+    if let Ok(addr) = std::env::var("UNDERTERM_SERVE") {
+        // every client gets its own runtime on its own thread
+        serve(addr, |mut rael| {
+            if let Ok(runtime) = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+            {
+                runtime.block_on(play(&mut rael));
+            }
+        })?;
+        return Ok(());
+    }
+    let mut rael = Rael::new(std::io::stdout(), "Rael Simple Example")?;
+    if let Ok(path) = std::env::var("UNDERTERM_CAST") {
+        rael.record_cast(path)?;
+    }
+    if let Ok(addr) = std::env::var("UNDERTERM_BROADCAST") {
+        rael.broadcast(&addr)?;
+    }
+    if let Ok(path) = std::env::var("UNDERTERM_CLIP") {
+        rael.record_clip(path, None)?;
+    }
+    if let Ok(path) = std::env::var("UNDERTERM_REPLAY") {
+        rael.replay_input(path)?;
+    } else if let Ok(path) = std::env::var("UNDERTERM_RECORD_INPUT") {
+        rael.record_input(path)?;
+    }
+    play(&mut rael).await;
+    //let _ = run_stress_test(&mut rael);
+    Ok(())
+}
+
+async fn play(rael: &mut Rael) {
+    let mut current_map = Map::Intro;
+    rael.force_clear();
+    let _ = rael.render(None).await;
+
+    loop {
+        if rael.shutdown_requested() {
+            break;
+        }
+        current_map = match current_map {
+            Map::Intro => underterm::introduction(rael).await,
+            Map::Menu => underterm::menu(rael).await,
+            Map::Exit => break,
+        }
+    }
+}
@@ -3,8 +3,8 @@
 // ▛▛▌▌▛▌▌▌▌▌▌
 // ▌▌▌▌▌▌▌▚▚▘▌ looks such a gud choice, but that mean i have to implement a way to add  this text
 //
-use crate::underterm::figlet::*;
 use rael::Color;
+use rael::FIGfont;
 use rael::Rael;
 
 #[derive(Clone, Eq, PartialEq)]
@@ -31,14 +31,13 @@ impl Scenario {
         bg: Color,
         cord: (usize, usize, u8),
         is_centered: Option<(bool, bool)>,
-        figlet_path: &str,
+        font: FIGfont,
         max_widht: u16,
     ) -> Self {
         let (x, mut y, z) = cord;
         if !y.is_multiple_of(2) {
             y += 1;
         }
-        let font = FIGfont::from_file(figlet_path).expect("failed to load figlet font");
         Self {
             bg,
             x,
@@ -94,13 +93,14 @@ impl Scenario {
             );
         }
 
+        let (width, height) = rael.canvas_size();
         if self.is_centered_x {
             let chars_len = rendered[0].lines().last().unwrap_or("").chars().count();
-            self.x = (rael.widht / 2) as usize - (chars_len / 2);
+            self.x = (width / 2).saturating_sub(chars_len / 2);
         }
         if self.is_centered_y {
             let lines: usize = rendered.iter().map(|b| b.lines().count()).sum();
-            self.y = (rael.height / 2) as usize - (lines / 2);
+            self.y = (height / 2).saturating_sub(lines / 2);
             if !self.y.is_multiple_of(2) {
                 self.y += 1;
             }
@@ -112,7 +112,7 @@ impl Scenario {
             let mut color_per_col = Vec::new();
             if self.is_centered_x {
                 let chars_len = rendered[ii].lines().last().unwrap_or("").chars().count();
-                self.x = (rael.widht / 2) as usize - (chars_len / 2);
+                self.x = (width / 2).saturating_sub(chars_len / 2);
             }
             for ch in phrase.chars() {
                 let mut current_pos = 0;
@@ -139,7 +139,7 @@ impl Scenario {
 
             let figure = self
                 .figlet
-                .convert(&phrase)
+                .convert(phrase)
                 .expect("figlet conversion failed");
             let figure_str = figure.to_string();
             let figlet_lines: Vec<&str> = figure_str.lines().collect();
@@ -1,13 +1,15 @@
 use crate::underterm::fonts::Scenario;
 use crate::underterm::fonts::StyledText;
 use crate::underterm::text::*;
-use crossterm::event::KeyEvent;
-use crossterm::event::KeyModifiers;
+use crossterm::event::KeyCode;
 use rael::Color;
+use rael::FIGfont;
+use rael::GameLoop;
 use rael::ImageAsset;
 use rael::Rael;
-use tokio::time::sleep;
-use tokio::time::Duration;
+use rael::Scaling;
+use std::ops::ControlFlow;
+use std::time::Duration;
 
 use crate::assets;
 use crate::underterm::text::TextCommand;
@@ -15,7 +17,6 @@ use assets::*;
 
 mod fonts;
 
-mod figlet;
 mod text;
 
 pub enum Map {
@@ -30,14 +31,22 @@ struct IntroScene {
 }
 
 pub async fn check_if_we_should_exit_aah(rael: &Rael) -> bool {
-    rael.inputs.snapshot().await.keys.contains(&KeyEvent::new(
-        crossterm::event::KeyCode::Enter,
-        KeyModifiers::NONE,
-    ))
+    rael.shutdown_requested() || rael.inputs.just_pressed(KeyCode::Enter)
+}
+
+// polls on the real time, the game clock can be paused or fast-forwarded
+async fn wait_until_we_should_exit(rael: &Rael) {
+    while !check_if_we_should_exit_aah(rael).await {
+        tokio::time::sleep(Duration::from_millis(2)).await;
+    }
 }
 
 pub async fn introduction(rael: &mut Rael) -> Map {
-    let center_w = (rael.widht / 2) - 60;
+    let clock = rael.clock.clone();
+    // the intro pictures are 120 pixels wide, the text goes under them
+    rael.set_logical_size(120, 100, Scaling::Integer);
+    let (width, height) = rael.canvas_size();
+    let center_w = width.saturating_sub(120) / 2;
     let intro_scenes: [IntroScene; 9] = [
         IntroScene {
             image: Some(INTRO_1),
@@ -116,9 +125,9 @@ pub async fn introduction(rael: &mut Rael) -> Map {
         }
         let mut scenario = Scenario::new(
             Color::new(0, 0, 0),
-            (0, (rael.height as usize / 2) + 27, 0),
+            (0, (height / 2) + 27, 0),
             Some((true, i == 4)),
-            "./src/underterm/default.flf",
+            FIGfont::standard().expect("failed to load figlet font"),
             120,
         );
 
@@ -134,41 +143,23 @@ pub async fn introduction(rael: &mut Rael) -> Map {
                     rael.clear();
 
                     if let Some(img) = scene.image {
-                        rael.set_image(img, (center_w as usize, 1, 0));
+                        rael.set_image(img, (center_w, 1, 0));
                     }
 
                     scenario.set_text(rael, &segments);
 
                     let _ = rael.render(None).await;
                     tokio::select! {
-                        _ = tokio::time::sleep(Duration::from_millis(40)) => {},
-                        _ = async {
-                            loop {
-                                if rael.inputs.snapshot().await.keys.contains(&KeyEvent::new(
-                                    crossterm::event::KeyCode::Enter,
-                                    KeyModifiers::NONE,
-                                )) {
-                                    break;
-                                }
-                                tokio::task::yield_now().await;
-                                sleep(Duration::from_millis(2)).await;
-                            }
-                        } => {
+                        _ = clock.sleep(Duration::from_millis(40)) => {},
+                        _ = wait_until_we_should_exit(rael) => {
                             should_exit = true;
                         }
                     };
                 }
                 WriterResult::Wait(dur) => {
                     tokio::select! {
-                        _ = tokio::time::sleep(dur) => {},
-                        _ = async {
-                            loop {
-                                if check_if_we_should_exit_aah(rael).await {
-                                    break;
-                                }
-                                tokio::task::yield_now().await;
-                            }
-                        } => {
+                        _ = clock.sleep(dur) => {},
+                        _ = wait_until_we_should_exit(rael) => {
                             should_exit = true;
                         }
                     };
@@ -181,18 +172,18 @@ pub async fn introduction(rael: &mut Rael) -> Map {
             for _ in 0..20 {
                 rael.force_clear();
                 if let Some(img) = scene.image {
-                    rael.set_image(img, (center_w as usize, 1, 0));
+                    rael.set_image(img, (center_w, 1, 0));
                 }
                 scenario.set_text(rael, &current_scene);
                 let _ = rael.render(Some(ii)).await;
                 ii -= 0.1;
-                sleep(Duration::from_millis(30)).await;
+                clock.sleep(Duration::from_millis(30)).await;
             }
             rael.force_clear();
             return Map::Menu;
         }
         if !should_exit {
-            sleep(Duration::from_secs(1)).await;
+            clock.sleep(Duration::from_secs(1)).await;
         }
     }
 
@@ -202,13 +193,32 @@ pub async fn introduction(rael: &mut Rael) -> Map {
 }
 
 pub async fn menu(rael: &mut Rael) -> Map {
-    for i in 0..rael.height as usize {
-        rael.set_pixel(i, i, 2, Color::new(255, 0, 0));
-        rael.set_pixel(i, rael.height as usize - i, 2, Color::new(255, 0, 0));
-    }
-
-    let _ = rael.render(None).await;
-    sleep(Duration::from_secs(2)).await;
+    rael.set_logical_size(64, 64, Scaling::Nearest);
+    let mut game_loop = GameLoop::new(rael.clock.clone(), 30.0);
+    game_loop.set_fps_cap(Some(30.0));
+    let mut shown = Duration::ZERO;
+
+    let _ = game_loop
+        .run(
+            rael,
+            |_, step| {
+                shown += step;
+                if shown >= Duration::from_secs(2) {
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue(())
+                }
+            },
+            |rael, _| {
+                rael.clear();
+                let (_, height) = rael.canvas_size();
+                for i in 0..height {
+                    rael.set_pixel(i, i, 2, Color::new(255, 0, 0));
+                    rael.set_pixel(i, height - 1 - i, 2, Color::new(255, 0, 0));
+                }
+            },
+        )
+        .await;
 
     rael.clear();
     let _ = rael.render(None).await;
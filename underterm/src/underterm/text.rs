@@ -1,6 +1,6 @@
 use crate::underterm::fonts::StyledText;
 use rael::Color;
-use tokio::time::Duration;
+use std::time::Duration;
 
 #[derive(Clone, PartialEq, Eq)]
 pub enum TextCommand {